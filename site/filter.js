@@ -2,7 +2,60 @@ const root = document.documentElement;
 const daySelect = document.getElementById("day");
 const hourSelect = document.getElementById("hour");
 
-root.dataset.selectedDayhour = `${daySelect.value}-${hourSelect.value}`;
-window.addEventListener("change", () => {
-  root.dataset.selectedDayhour = `${daySelect.value}-${hourSelect.value}`;
-});
+// Bitmaps of when each listing is on, see FilterIndex in the generator.
+// null if it can't be loaded, every listing is then shown
+const filterIndex = root.dataset.filterIndex
+  ? fetch(root.dataset.filterIndex)
+      .then((response) => (response.ok ? response.json() : null))
+      .catch(() => null)
+  : null;
+
+function bitSet(hex, bit) {
+  const digit = hex[hex.length - 1 - Math.floor(bit / 4)];
+  return digit !== undefined && (parseInt(digit, 16) & (1 << bit % 4)) !== 0;
+}
+
+function selects(index, key, day, hour) {
+  const bitmaps = index.bitmaps[key];
+  if (!bitmaps) {
+    return false;
+  }
+  const bit = hour === "all" ? null : index.slots.indexOf(hour);
+  if (bit === -1) {
+    return false;
+  }
+  return bitmaps.some(
+    (bitmap, d) =>
+      (day === "all" || Number(day) === d) &&
+      (bit === null ? bitmap !== "0" : bitSet(bitmap, bit)),
+  );
+}
+
+// style.css hides unmatched listings by selectors when filtering by the hour,
+// finer slots and the filter index are marked here instead to keep the stylesheet small
+async function filter() {
+  const day = daySelect.value;
+  const hour = hourSelect.value;
+  root.dataset.selectedDayhour = `${day}-${hour}`;
+  const index = await filterIndex;
+  if (index) {
+    for (const element of document.querySelectorAll("[data-filter]")) {
+      element.classList.toggle(
+        "unmatched",
+        !selects(index, element.dataset.filter, day, hour),
+      );
+    }
+    return;
+  }
+  const dayhour = `${day}-${hour}`;
+  for (const element of document.querySelectorAll("[data-daytimes]")) {
+    const daytimes = element.dataset.daytimes.split(" ");
+    element.classList.toggle(
+      "unmatched",
+      dayhour !== "all-all" && !daytimes.includes(dayhour),
+    );
+  }
+}
+
+filter();
+window.addEventListener("change", filter);
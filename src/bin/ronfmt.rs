@@ -1,19 +1,26 @@
 use std::fs;
 
 use anyhow::{Result, anyhow};
-use ron::ser::PrettyConfig;
-use sitegen::{Restaurants, ron_options};
+use sitegen::{Restaurants, TimesFormat, ron_options};
 
+/// Reformat a restaurants file in place.
+/// Times keep their current form unless `--text` or `--tuples` is given.
 fn main() -> Result<()> {
-    let Some(path) = std::env::args().nth(1) else {
-        return Err(anyhow!("Specify ron pathname"));
+    let mut format = None;
+    let mut path = None;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--text" => format = Some(TimesFormat::Text),
+            "--tuples" => format = Some(TimesFormat::Tuples),
+            _ => path = Some(arg),
+        }
+    }
+    let Some(path) = path else {
+        return Err(anyhow!("Specify ron pathname [--text | --tuples]"));
     };
-    let ro = ron_options();
-    let restaurants: Restaurants = ro.from_str(&fs::read_to_string(&path)?)?;
-    let pretty = PrettyConfig::new()
-        .depth_limit(4)
-        .separate_tuple_members(true)
-        .enumerate_arrays(false);
-    fs::write(path, ro.to_string_pretty(&restaurants, pretty)?)?;
+    let source = fs::read_to_string(&path)?;
+    let restaurants: Restaurants<String> = ron_options().from_str(&source)?;
+    let format = format.unwrap_or_else(|| TimesFormat::detect(&source));
+    fs::write(path, restaurants.to_ron(format)?)?;
     Ok(())
 }
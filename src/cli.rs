@@ -1,9 +1,90 @@
-use clap::{Parser, ValueHint};
+use clap::{Parser, Subcommand, ValueEnum, ValueHint};
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(version, about="Static website generator", long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub paths: SitePaths,
+    #[command(flatten)]
+    pub build: BuildFlags,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Build the site, then rebuild whenever a source file changes
+    Watch {
+        #[command(flatten)]
+        paths: SitePaths,
+        #[command(flatten)]
+        build: BuildFlags,
+    },
+    /// Serve the site locally, rebuilding and reloading the browser on changes
+    Serve(ServeArgs),
+    /// Validate the restaurant data, reporting all problems found
+    Check {
+        /// Source site directory
+        #[arg(default_value = "./site", value_hint = ValueHint::DirPath)]
+        site: PathBuf,
+    },
+    /// List restaurants not verified recently, oldest first
+    Stale {
+        /// Source site directory
+        #[arg(default_value = "./site", value_hint = ValueHint::DirPath)]
+        site: PathBuf,
+        /// Report restaurants verified longer ago than this, e.g. 180d or 26w
+        #[arg(long, default_value = "180d", value_parser = parse_days)]
+        older_than: i64,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Add a restaurant, prompting for each field
+    Add {
+        /// Source site directory
+        #[arg(default_value = "./site", value_hint = ValueHint::DirPath)]
+        site: PathBuf,
+    },
+    /// Edit a restaurant, prompting for each field with the current value as default
+    Edit {
+        /// Name of the restaurant, ignoring case
+        name: String,
+        /// Source site directory
+        #[arg(default_value = "./site", value_hint = ValueHint::DirPath)]
+        site: PathBuf,
+    },
+    /// Compare two restaurant data files, listing changes by restaurant
+    Diff {
+        /// Previous restaurants.ron
+        #[arg(value_hint = ValueHint::FilePath)]
+        old: PathBuf,
+        /// Current restaurants.ron
+        #[arg(default_value = "./site/_data/restaurants.ron", value_hint = ValueHint::FilePath)]
+        new: PathBuf,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// List restaurants in happy hour now, or at a given time
+    Now {
+        /// Source site directory
+        #[arg(default_value = "./site", value_hint = ValueHint::DirPath)]
+        site: PathBuf,
+        /// Time to check, e.g. "2025-08-01 18:00" (local time) or an RFC 3339 timestamp
+        #[arg(long)]
+        at: Option<String>,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SitePaths {
     /// Source site directory
     #[arg(default_value = "./site", value_hint = ValueHint::DirPath)]
     pub site: PathBuf,
@@ -11,3 +92,54 @@ pub struct Args {
     #[arg(default_value = "./output", value_hint = ValueHint::DirPath)]
     pub output: PathBuf,
 }
+
+#[derive(clap::Args, Debug)]
+pub struct ServeArgs {
+    /// Source site directory
+    #[arg(default_value = "./site", value_hint = ValueHint::DirPath)]
+    pub site: PathBuf,
+    /// Output build directory, defaults to a temporary directory
+    #[arg(short, long, value_hint = ValueHint::DirPath)]
+    pub output: Option<PathBuf>,
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+    /// Port to listen on
+    #[arg(short, long, default_value_t = 8000)]
+    pub port: u16,
+    #[command(flatten)]
+    pub build: BuildFlags,
+}
+
+/// Optional outputs of a build.
+#[derive(clap::Args, Debug)]
+pub struct BuildFlags {
+    /// Write iCalendar feeds of the happy hours
+    #[arg(long)]
+    pub calendars: bool,
+    /// Write an Atom feed of listing changes since the previous build to the same output
+    #[arg(long)]
+    pub feed: bool,
+    /// Write sitemap.xml and robots.txt listing everything in the output
+    #[arg(long)]
+    pub sitemap: bool,
+    /// Keep files left in the output by previous builds that this build doesn't write.
+    /// The previous output is copied into staging first, which takes longer for large sites.
+    #[arg(long)]
+    pub keep: bool,
+}
+
+/// Parse a number of days, with an optional `d` (days) or `w` (weeks) suffix.
+fn parse_days(s: &str) -> Result<i64, String> {
+    let (number, multiplier) = if let Some(days) = s.strip_suffix('d') {
+        (days, 1)
+    } else if let Some(weeks) = s.strip_suffix('w') {
+        (weeks, 7)
+    } else {
+        (s, 1)
+    };
+    number
+        .parse::<i64>()
+        .map(|n| n * multiplier)
+        .map_err(|_| format!("invalid duration {s:?}, expected e.g. 180d or 26w"))
+}
@@ -0,0 +1,107 @@
+use anyhow::{Context, Result, bail};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use crate::ron_options;
+
+/// Name of the configuration file in the site directory.
+pub const CONFIG_FILE: &str = "_config.ron";
+
+/// Site-wide settings from `_config.ron`, available to templates as `site`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Config {
+    /// Absolute URL the site is published at, without a trailing slash
+    pub base_url: String,
+    pub title: String,
+    pub description: String,
+    /// Region assumed for phone numbers without a country code, e.g. `US`
+    pub phone_region: phonenumber::country::Id,
+    /// Happy hours are listed in local time for this timezone, e.g. "America/New_York"
+    pub timezone: chrono_tz::Tz,
+    /// Social preview image, relative to `base_url`
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Where visitors can submit updates
+    #[serde(default)]
+    pub feedback_url: Option<String>,
+    #[serde(default)]
+    pub instagram_id: Option<String>,
+    /// Minutes per step of the day/time filter, 60 filters by the hour, 30 or 15 for finer steps
+    #[serde(default = "default_slot_minutes")]
+    pub slot_minutes: u16,
+    /// Filter with a generated `filter-index.json` rather than per-listing tokens and CSS selectors
+    #[serde(default)]
+    pub filter_index: bool,
+}
+
+fn default_slot_minutes() -> u16 {
+    crate::model::HOUR_SLOT
+}
+
+impl Config {
+    /// Load `_config.ron` from the site directory.
+    pub fn load(site: &Path) -> Result<Self> {
+        let path = site.join(CONFIG_FILE);
+        let source = fs::read_to_string(&path).context(format!("{path:?}"))?;
+        let config: Self = ron_options()
+            .from_str(&source)
+            .with_context(|| format!("{}", path.display()))?;
+        config
+            .validate()
+            .with_context(|| format!("{}", path.display()))?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if ![15, 30, 60].contains(&self.slot_minutes) {
+            bail!(
+                "invalid slot_minutes {}, expected 15, 30 or 60",
+                self.slot_minutes
+            );
+        }
+        Ok(())
+    }
+
+    /// The current date in the site timezone.
+    pub fn today(&self) -> NaiveDate {
+        chrono::Utc::now()
+            .with_timezone(&self.timezone)
+            .date_naive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config() {
+        let config: Config = ron_options()
+            .from_str(
+                r#"(
+    base_url: "https://example.com",
+    title: "Happy Hours",
+    description: "All the happy hours.",
+    phone_region: GB,
+    timezone: "Europe/London",
+    instagram_id: Some("happyhours"),
+)"#,
+            )
+            .unwrap();
+        assert_eq!(config.phone_region, phonenumber::country::GB);
+        assert_eq!(config.timezone, chrono_tz::Europe::London);
+        assert_eq!(config.image, None);
+        assert_eq!(config.slot_minutes, 60);
+        assert!(!config.filter_index);
+        assert!(config.validate().is_ok());
+        for slot_minutes in [0, 1, 5, 20, 45] {
+            let config = Config {
+                slot_minutes,
+                ..config.clone()
+            };
+            assert!(config.validate().is_err());
+        }
+        assert_eq!(serde_json::to_value(&config).unwrap()["phone_region"], "GB");
+    }
+}
@@ -0,0 +1,267 @@
+use std::{error::Error as _, fmt::Display, ops::Range};
+
+/// Templates that failed to compile or render, reported together.
+#[derive(Debug)]
+pub struct TemplateErrors(pub Vec<TemplateError>);
+
+/// A template error, located in the source of the template that raised it.
+#[derive(Debug, PartialEq)]
+pub struct TemplateError {
+    /// Template being compiled or rendered, e.g. `restaurant/[slug].html`
+    pub template: String,
+    /// Output being rendered, if rendering
+    pub output: Option<String>,
+    /// What went wrong, e.g. "undefined value"
+    pub message: String,
+    /// Where it went wrong, then each include or import leading there
+    pub frames: Vec<Frame>,
+}
+
+/// A position in a template, with the source line it points at.
+#[derive(Debug, PartialEq)]
+pub struct Frame {
+    pub template: String,
+    pub line: usize,
+    /// One-based, in characters
+    pub column: usize,
+    pub source_line: String,
+    /// Characters from `column` that are in error
+    pub width: usize,
+    /// What happened here, e.g. "could not render include"
+    pub label: String,
+}
+
+impl TemplateError {
+    /// Describe a minijinja error raised while compiling `template`,
+    /// or rendering it to `output`.
+    pub fn new(err: &minijinja::Error, template: &str, output: Option<&str>) -> Self {
+        // Outermost first, an error in an included template is the source of the include error
+        let mut chain = vec![err];
+        let mut current = err;
+        while let Some(source) = current
+            .source()
+            .and_then(|source| source.downcast_ref::<minijinja::Error>())
+        {
+            chain.push(source);
+            current = source;
+        }
+        let innermost = chain.last().expect("error chain");
+        Self {
+            template: template.to_owned(),
+            output: output.map(str::to_owned),
+            message: describe(innermost),
+            frames: chain.iter().rev().filter_map(|err| frame(err)).collect(),
+        }
+    }
+}
+
+impl Frame {
+    /// Point at `line` and `column` of a template's `source`, if the line exists.
+    pub fn in_source(
+        template: &str,
+        source: &str,
+        (line, column, width): (usize, usize, usize),
+        label: &str,
+    ) -> Option<Self> {
+        let source_line = source.lines().nth(line.checked_sub(1)?)?;
+        Some(Self {
+            template: template.to_owned(),
+            line,
+            column,
+            source_line: source_line.to_owned(),
+            width: width.max(1),
+            label: label.to_owned(),
+        })
+    }
+}
+
+fn describe(err: &minijinja::Error) -> String {
+    match err.detail() {
+        Some(detail) => format!("{}: {detail}", err.kind()),
+        None => err.kind().to_string(),
+    }
+}
+
+fn frame(err: &minijinja::Error) -> Option<Frame> {
+    let template = err.name()?;
+    let line = err.line()?;
+    let source_line = err.template_source()?.lines().nth(line.checked_sub(1)?)?;
+    let (column, width) = match err.range() {
+        Some(range) => columns(err.template_source()?, line, range),
+        None => (1, source_line.chars().count()),
+    };
+    Some(Frame {
+        template: template.to_owned(),
+        line,
+        column,
+        source_line: source_line.to_owned(),
+        width: width.max(1),
+        label: err.kind().to_string(),
+    })
+}
+
+/// Column and width in characters of the byte `range` within `line`, up to the end of that line.
+fn columns(source: &str, line: usize, range: Range<usize>) -> (usize, usize) {
+    let start_of_line: usize = source
+        .split_inclusive('\n')
+        .take(line - 1)
+        .map(str::len)
+        .sum();
+    let line_source = source[start_of_line..].lines().next().unwrap_or_default();
+    let start = range
+        .start
+        .saturating_sub(start_of_line)
+        .min(line_source.len());
+    let end = range
+        .end
+        .saturating_sub(start_of_line)
+        .min(line_source.len());
+    let chars = |bytes: Range<usize>| line_source.get(bytes).map_or(0, |s| s.chars().count());
+    (chars(0..start) + 1, chars(start..end))
+}
+
+impl Display for TemplateError {
+    /// Formatted like a compiler error, e.g.
+    ///
+    /// ```text
+    /// error: undefined value
+    ///  --> macros.html:2:4
+    ///   |
+    /// 2 | {{ x.a.b }}
+    ///   |    ^^^^^ undefined value
+    ///   = note: rendering restaurant/the-black-swan.html from restaurant/[slug].html
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "error: {}", self.message)?;
+        let gutter = self
+            .frames
+            .iter()
+            .map(|frame| frame.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        for (index, frame) in self.frames.iter().enumerate() {
+            let arrow = if index == 0 { "-->" } else { ":::" };
+            writeln!(
+                f,
+                "{:gutter$}{arrow} {}:{}:{}",
+                "", frame.template, frame.line, frame.column
+            )?;
+            writeln!(f, "{:gutter$} |", "")?;
+            writeln!(f, "{:>gutter$} | {}", frame.line, frame.source_line)?;
+            writeln!(
+                f,
+                "{:gutter$} | {:indent$}{} {}",
+                "",
+                "",
+                "^".repeat(frame.width),
+                frame.label,
+                indent = frame.column - 1
+            )?;
+        }
+        match &self.output {
+            Some(output) if *output != self.template => write!(
+                f,
+                "{:gutter$} = note: rendering {output} from {}",
+                "", self.template
+            ),
+            Some(_) => write!(f, "{:gutter$} = note: rendering {}", "", self.template),
+            None => write!(f, "{:gutter$} = note: compiling {}", "", self.template),
+        }
+    }
+}
+
+impl Display for TemplateErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for error in &self.0 {
+            writeln!(f, "{error}\n")?;
+        }
+        write!(f, "{} template(s) failed", self.0.len())
+    }
+}
+
+impl std::error::Error for TemplateErrors {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minijinja::{Environment, context};
+
+    fn render_error(env: &Environment, name: &str) -> TemplateError {
+        let err = env
+            .get_template(name)
+            .unwrap()
+            .render(context! {})
+            .unwrap_err();
+        TemplateError::new(&err, name, Some(name))
+    }
+
+    #[test]
+    fn test_template_error() {
+        let mut env = Environment::new();
+        env.set_debug(true);
+        env.add_template("inc.html", "<p>\n  {{ missing.attr }}</p>\n")
+            .unwrap();
+        env.add_template("page.html", "<h1>é</h1>\n{% include 'inc.html' %}")
+            .unwrap();
+        let error = render_error(&env, "page.html");
+        assert_eq!(error.message, "undefined value");
+        assert_eq!(
+            error
+                .frames
+                .iter()
+                .map(|f| (f.template.as_str(), f.line, f.column, f.width))
+                .collect::<Vec<_>>(),
+            vec![("inc.html", 2, 6, 12), ("page.html", 2, 4, 18)]
+        );
+        assert_eq!(
+            error.to_string(),
+            "error: undefined value
+ --> inc.html:2:6
+  |
+2 |   {{ missing.attr }}</p>
+  |      ^^^^^^^^^^^^ undefined value
+ ::: page.html:2:4
+  |
+2 | {% include 'inc.html' %}
+  |    ^^^^^^^^^^^^^^^^^^ could not render include
+  = note: rendering page.html"
+        );
+
+        env.add_template(
+            "macros.html",
+            "{% macro m(x) %}\n{{ x + 'a' }}\n{% endmacro %}",
+        )
+        .unwrap();
+        env.add_template(
+            "restaurant/[slug].html",
+            "{% from 'macros.html' import m %}{{ m(1) }}",
+        )
+        .unwrap();
+        let err = env
+            .get_template("restaurant/[slug].html")
+            .unwrap()
+            .render(context! {})
+            .unwrap_err();
+        let error = TemplateError::new(&err, "restaurant/[slug].html", Some("restaurant/a.html"));
+        assert_eq!(
+            error.to_string(),
+            "error: invalid operation: tried to use + operator on unsupported types number and string
+ --> macros.html:2:4
+  |
+2 | {{ x + 'a' }}
+  |    ^^^^^^^ invalid operation
+  = note: rendering restaurant/a.html from restaurant/[slug].html"
+        );
+
+        let err = env.add_template("bad.html", "ok\n{% if %}").unwrap_err();
+        assert_eq!(
+            TemplateError::new(&err, "bad.html", None).to_string(),
+            "error: syntax error: unexpected end of block
+ --> bad.html:2:7
+  |
+2 | {% if %}
+  |       ^^ syntax error
+  = note: compiling bad.html"
+        );
+    }
+}
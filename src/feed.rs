@@ -0,0 +1,171 @@
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, fs, path::Path};
+
+use crate::{config::Config, model::Change, sitemap::escape};
+
+/// Atom feed of listing changes, written to the output.
+pub const FEED_FILE: &str = "feed.xml";
+/// Every entry in the feed, kept in the output so entries accumulate across builds.
+pub const HISTORY_FILE: &str = "feed-history.json";
+
+/// Entries older than this many are dropped from the feed.
+const MAX_ENTRIES: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FeedEntry {
+    pub id: String,
+    pub updated: NaiveDate,
+    pub title: String,
+    pub summary: Option<String>,
+    pub link: String,
+}
+
+impl FeedEntry {
+    /// An entry for `change` found on `updated`, linking to `link`.
+    pub fn new(change: &Change, updated: NaiveDate, config: &Config, link: String) -> Result<Self> {
+        let host = url::Url::parse(&config.base_url)?
+            .host_str()
+            .unwrap_or_default()
+            .to_owned();
+        Ok(Self {
+            id: format!("tag:{host},{updated}:{}/{}", change.kind(), change.slug()),
+            updated,
+            title: change.to_string(),
+            summary: change.summary(),
+            link,
+        })
+    }
+}
+
+/// Previous feed entries, newest first, or none if there is no history yet.
+pub fn load_history(path: &Path) -> Result<Vec<FeedEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let history = fs::read_to_string(path).with_context(|| format!("{}", path.display()))?;
+    serde_json::from_str(&history).with_context(|| format!("{}", path.display()))
+}
+
+/// Add `entries` to the front of `history`, skipping any already recorded.
+pub fn add_entries(history: &mut Vec<FeedEntry>, entries: Vec<FeedEntry>) {
+    let entries: Vec<_> = entries
+        .into_iter()
+        .filter(|entry| !history.iter().any(|old| old.id == entry.id))
+        .collect();
+    history.splice(0..0, entries);
+    history.truncate(MAX_ENTRIES);
+}
+
+/// An Atom feed of `entries`, last updated on `updated`.
+pub fn atom(config: &Config, entries: &[FeedEntry], updated: NaiveDate) -> Result<String> {
+    let base_url = config.base_url.trim_end_matches('/');
+    let timestamp = |date: NaiveDate| {
+        config
+            .timezone
+            .from_local_datetime(&date.and_time(NaiveTime::MIN))
+            .earliest()
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_else(|| format!("{date}T00:00:00Z"))
+    };
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    writeln!(xml, "<feed xmlns=\"http://www.w3.org/2005/Atom\">")?;
+    writeln!(xml, "  <id>{}/</id>", escape(base_url))?;
+    writeln!(xml, "  <title>{}</title>", escape(&config.title))?;
+    writeln!(
+        xml,
+        "  <subtitle>{}</subtitle>",
+        escape(&config.description)
+    )?;
+    writeln!(xml, "  <link href=\"{}/\"/>", escape(base_url))?;
+    writeln!(
+        xml,
+        "  <link rel=\"self\" href=\"{}/{FEED_FILE}\"/>",
+        escape(base_url)
+    )?;
+    writeln!(xml, "  <updated>{}</updated>", timestamp(updated))?;
+    writeln!(
+        xml,
+        "  <author><name>{}</name></author>",
+        escape(&config.title)
+    )?;
+    for entry in entries {
+        writeln!(xml, "  <entry>")?;
+        writeln!(xml, "    <id>{}</id>", escape(&entry.id))?;
+        writeln!(xml, "    <title>{}</title>", escape(&entry.title))?;
+        writeln!(xml, "    <link href=\"{}\"/>", escape(&entry.link))?;
+        writeln!(xml, "    <updated>{}</updated>", timestamp(entry.updated))?;
+        if let Some(summary) = &entry.summary {
+            writeln!(xml, "    <summary>{}</summary>", escape(summary))?;
+        }
+        writeln!(xml, "  </entry>")?;
+    }
+    writeln!(xml, "</feed>")?;
+    Ok(xml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str) -> FeedEntry {
+        FeedEntry {
+            id: id.to_owned(),
+            updated: NaiveDate::from_ymd_opt(2025, 9, 1).unwrap(),
+            title: "New happy hours at Swan & Co".to_owned(),
+            summary: Some("Mon-Fri 4pm-6pm → Mon-Fri 3pm-6pm".to_owned()),
+            link: "https://example.com/restaurant/swan-co.html".to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_add_entries() {
+        let mut history = vec![entry("b"), entry("a")];
+        add_entries(&mut history, vec![entry("c"), entry("b")]);
+        let ids: Vec<_> = history.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_atom() {
+        let config: Config = crate::ron_options()
+            .from_str(
+                r#"(
+    base_url: "https://example.com",
+    title: "Happy Hours",
+    description: "All the happy hours.",
+    phone_region: US,
+    timezone: "America/New_York",
+)"#,
+            )
+            .unwrap();
+        let updated = NaiveDate::from_ymd_opt(2025, 9, 2).unwrap();
+        assert_eq!(
+            atom(
+                &config,
+                &[entry("tag:example.com,2025-09-01:retimed/swan-co")],
+                updated
+            )
+            .unwrap(),
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <id>https://example.com/</id>
+  <title>Happy Hours</title>
+  <subtitle>All the happy hours.</subtitle>
+  <link href="https://example.com/"/>
+  <link rel="self" href="https://example.com/feed.xml"/>
+  <updated>2025-09-02T00:00:00-04:00</updated>
+  <author><name>Happy Hours</name></author>
+  <entry>
+    <id>tag:example.com,2025-09-01:retimed/swan-co</id>
+    <title>New happy hours at Swan &amp; Co</title>
+    <link href="https://example.com/restaurant/swan-co.html"/>
+    <updated>2025-09-01T00:00:00-04:00</updated>
+    <summary>Mon-Fri 4pm-6pm → Mon-Fri 3pm-6pm</summary>
+  </entry>
+</feed>
+"#
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use serde::Deserialize;
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::ron_options;
+
+const DELIMITER: &str = "---";
+
+/// Settings in RON between `---` lines at the top of a template.
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct FrontMatter {
+    /// Output path instead of the template name, may contain `[slug]`
+    pub output: Option<String>,
+    /// `false` for layouts and macros only used by other templates
+    pub render: bool,
+    /// Added to the context the template is rendered with
+    pub vars: BTreeMap<String, ron::Value>,
+}
+
+impl Default for FrontMatter {
+    fn default() -> Self {
+        Self {
+            output: None,
+            render: true,
+            vars: BTreeMap::new(),
+        }
+    }
+}
+
+/// Front matter that could not be parsed, located in the template.
+#[derive(Debug)]
+pub(crate) struct FrontMatterError {
+    pub message: String,
+    pub line: usize,
+    /// One-based, in characters
+    pub column: usize,
+    /// Characters from `column` that are in error
+    pub width: usize,
+}
+
+impl FrontMatter {
+    /// Split the front matter from the template source, if it has any.
+    /// The front matter becomes a comment spanning the same lines, so line numbers in errors still match.
+    pub fn parse(source: &str) -> Result<(Self, String), FrontMatterError> {
+        let mut lines = source.split_inclusive('\n');
+        if lines.next().map(str::trim_end) != Some(DELIMITER) {
+            return Ok((Self::default(), source.to_owned()));
+        }
+        let mut ron = String::new();
+        let mut newlines = 1;
+        for line in lines.by_ref() {
+            newlines += 1;
+            if line.trim_end() == DELIMITER {
+                let front_matter = ron_options().from_str(&ron).map_err(|err| {
+                    let (start, end) = (err.span.start, err.span.end);
+                    let width = if end.line == start.line {
+                        end.col.saturating_sub(start.col)
+                    } else {
+                        1
+                    };
+                    // The span may start at whitespace before the value in error
+                    let skipped = ron
+                        .lines()
+                        .nth(start.line.saturating_sub(1))
+                        .map_or(0, |line| {
+                            line.chars()
+                                .skip(start.col.saturating_sub(1))
+                                .take_while(|c| c.is_whitespace())
+                                .count()
+                        });
+                    FrontMatterError {
+                        message: format!("invalid front matter: {}", err.code),
+                        // The RON starts on the line after the opening delimiter
+                        line: start.line + 1,
+                        column: start.col.max(1) + skipped,
+                        width: width.saturating_sub(skipped).max(1),
+                    }
+                })?;
+                let rest: String = lines.collect();
+                return Ok((
+                    front_matter,
+                    format!("{{#{}#}}{rest}", "\n".repeat(newlines)),
+                ));
+            }
+            ron.push_str(line);
+        }
+        Err(FrontMatterError {
+            message: format!("front matter is missing its closing {DELIMITER}"),
+            line: 1,
+            column: 1,
+            width: DELIMITER.len(),
+        })
+    }
+}
+
+impl Display for FrontMatterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for FrontMatterError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minijinja::{Environment, context};
+
+    #[test]
+    fn test_front_matter() {
+        let (front_matter, body) = FrontMatter::parse(
+            "---
+(
+    output: Some(\"about/index.html\"),
+    vars: {\"title\": \"About\"},
+)
+---
+<h1>{{ title }}</h1>
+",
+        )
+        .unwrap();
+        assert_eq!(front_matter.output.as_deref(), Some("about/index.html"));
+        assert!(front_matter.render);
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        let vars = minijinja::Value::from_serialize(&front_matter.vars);
+        assert_eq!(
+            env.render_str(&body, context! { ..vars }).unwrap(),
+            "<h1>About</h1>"
+        );
+
+        let (front_matter, body) =
+            FrontMatter::parse("---\n(render: false)\n---\n{{ missing.attr }}\n").unwrap();
+        assert!(!front_matter.render);
+        let err = env.render_str(&body, context! {}).unwrap_err();
+        assert_eq!(err.line(), Some(4));
+
+        let source = "<p>--- no front matter</p>\n";
+        assert_eq!(
+            FrontMatter::parse(source).unwrap(),
+            (FrontMatter::default(), source.to_owned())
+        );
+        assert!(FrontMatter::parse("---\n(render: false)\n").is_err());
+        let err = FrontMatter::parse("---\n(render: true,\n unknown: 1)\n---\n").unwrap_err();
+        assert_eq!(err.line, 3);
+    }
+}
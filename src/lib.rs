@@ -1,12 +1,35 @@
 mod cli;
+mod config;
+mod diagnostic;
+mod feed;
+mod front_matter;
 mod model;
+mod prompt;
+mod serve;
 mod site;
+mod sitemap;
+mod watch;
 
-pub use cli::Args;
-pub use model::Restaurants;
-use ron::{Options, extensions::Extensions};
-pub use site::SiteGenerator;
+pub use cli::{Args, BuildFlags, Command, OutputFormat, ServeArgs, SitePaths};
+pub use config::{CONFIG_FILE, Config};
+pub use diagnostic::{Frame, TemplateError, TemplateErrors};
+pub use model::{
+    Diff, FilterIndex, Phone, Problem, Restaurants, StaleReport, TimesFormat, check, parse_datetime,
+};
+pub use prompt::Prompter;
+use ron::{Options, extensions::Extensions, ser::PrettyConfig};
+pub use serve::{PreviewServer, Reloader};
+pub use site::{SiteGenerator, load_restaurants, read_restaurants, write_restaurants};
+pub use watch::watch;
 
 pub fn ron_options() -> Options {
     Options::default().with_default_extension(Extensions::UNWRAP_NEWTYPES)
 }
+
+/// Formatting of `restaurants.ron`, shared by `ronfmt` and commands that rewrite it.
+pub fn ron_pretty() -> PrettyConfig {
+    PrettyConfig::new()
+        .depth_limit(4)
+        .separate_tuple_members(true)
+        .enumerate_arrays(false)
+}
@@ -1,6 +1,15 @@
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
 use clap::Parser;
-use sitegen::{Args, SiteGenerator};
+use sitegen::{
+    Args, BuildFlags, Command, Config, OutputFormat, PreviewServer, Prompter, Restaurants,
+    ServeArgs, SiteGenerator, StaleReport, TimesFormat, check, load_restaurants, parse_datetime,
+    read_restaurants, ron_options, watch, write_restaurants,
+};
+use std::{
+    fs,
+    io::{self, StdinLock, Stdout},
+    path::Path,
+};
 
 fn main() {
     let args = Args::parse();
@@ -11,12 +20,130 @@ fn main() {
 }
 
 fn run(args: Args) -> Result<()> {
-    if !std::env::current_dir().unwrap().join(&args.site).is_dir() {
+    match args.command {
+        None => {
+            let generator = generator(&args.paths.site, &args.build)?;
+            generator.build(args.paths.output)
+        }
+        Some(Command::Watch { paths, build }) => {
+            let mut generator = generator(&paths.site, &build)?;
+            watch(&mut generator, paths.output, || {})
+        }
+        Some(Command::Serve(args)) => serve(args),
+        Some(Command::Check { site }) => check_site(&site),
+        Some(Command::Stale {
+            site,
+            older_than,
+            format,
+        }) => {
+            let config = Config::load(&site)?;
+            let restaurants = load_restaurants(&site, config.phone_region)?;
+            let report = StaleReport::new(&restaurants, config.today(), older_than);
+            match format {
+                OutputFormat::Text => print!("{report}"),
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            }
+            Ok(())
+        }
+        Some(Command::Add { site }) => edit_site(&site, |restaurants, prompter, config| {
+            let name =
+                restaurants.add_interactive(prompter, config.today(), config.phone_region)?;
+            eprintln!("Added {name}");
+            Ok(())
+        }),
+        Some(Command::Edit { name, site }) => edit_site(&site, |restaurants, prompter, config| {
+            restaurants.edit_interactive(&name, prompter, config.today(), config.phone_region)
+        }),
+        Some(Command::Diff { old, new, format }) => {
+            let diff = read_restaurants(&new)?.diff(&read_restaurants(&old)?);
+            match format {
+                OutputFormat::Text => print!("{diff}"),
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+            }
+            Ok(())
+        }
+        Some(Command::Now { site, at }) => {
+            let config = Config::load(&site)?;
+            let tz = config.timezone;
+            let restaurants = load_restaurants(&site, config.phone_region)?;
+            let when = match at {
+                Some(at) => parse_datetime(&at, tz).map_err(|err| anyhow!(err))?,
+                None => chrono::Utc::now().with_timezone(&tz),
+            };
+            for name in restaurants.happening_at(&when, tz) {
+                println!("{name}");
+            }
+            Ok(())
+        }
+    }
+}
+
+fn check_site(site: &Path) -> Result<()> {
+    let ronpath = site.join("_data/restaurants.ron");
+    let source = fs::read_to_string(&ronpath).context(format!("{ronpath:?}"))?;
+    let config = Config::load(site)?;
+    let problems = check(&source, config.today(), config.phone_region);
+    for problem in &problems {
+        println!("{}:{problem}", ronpath.display());
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("{} problem(s) found", problems.len()))
+    }
+}
+
+/// Interactively change the site's restaurants, then write them back.
+fn edit_site(
+    site: &Path,
+    edit: impl FnOnce(&mut Restaurants, &mut Prompter<StdinLock, Stdout>, &Config) -> Result<()>,
+) -> Result<()> {
+    let config = Config::load(site)?;
+    let ronpath = site.join("_data/restaurants.ron");
+    let source = fs::read_to_string(&ronpath).context(format!("{ronpath:?}"))?;
+    let mut restaurants = ron_options()
+        .from_str::<Restaurants<String>>(&source)
+        .map_err(anyhow::Error::from)
+        .and_then(|restaurants| restaurants.parse_phones(config.phone_region))
+        .with_context(|| format!("{}", ronpath.display()))?;
+    let mut prompter = Prompter::new(io::stdin().lock(), io::stdout());
+    edit(&mut restaurants, &mut prompter, &config)?;
+    write_restaurants(&ronpath, &restaurants, TimesFormat::detect(&source))
+}
+
+fn serve(args: ServeArgs) -> Result<()> {
+    let mut generator = generator(&args.site, &args.build)?;
+    let tempdir;
+    let output = match args.output {
+        Some(ref output) => output.as_path(),
+        None => {
+            tempdir = tempfile::tempdir()?;
+            tempdir.path()
+        }
+    };
+    let server = PreviewServer::bind((args.host.as_str(), args.port), output)?;
+    if let Some(addr) = server.addr() {
+        eprintln!("Serving {} at http://{addr}/", output.display());
+    }
+    let reloader = server.reloader();
+    std::thread::scope(|scope| {
+        scope.spawn(|| server.run());
+        let result = watch(&mut generator, output, || reloader.reload());
+        server.shutdown();
+        result
+    })
+}
+
+fn generator<'a>(site: &Path, build: &BuildFlags) -> Result<SiteGenerator<'a>> {
+    if !std::env::current_dir().unwrap().join(site).is_dir() {
         return Err(anyhow!(
             "Invalid site directory: {}",
-            args.site.to_str().unwrap()
+            site.to_str().unwrap()
         ));
     }
-    let generator = SiteGenerator::new(args.site)?;
-    generator.build(args.output)
+    Ok(SiteGenerator::new(site)?
+        .with_calendars(build.calendars)
+        .with_feed(build.feed)
+        .with_sitemap(build.sitemap)
+        .with_clean(!build.keep))
 }
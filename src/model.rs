@@ -1,21 +1,55 @@
 use chrono::NaiveDate;
-use serde::{Deserialize, Deserializer, Serialize};
-use std::{collections::BTreeSet, fmt::Display, ops::Range};
-
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, fmt::Display, iter::StepBy, ops::Range};
+
+mod calendar;
+mod check;
+mod diff;
+mod edit;
+mod filter;
+mod functions;
+mod jsonld;
+mod now;
 mod object;
-pub use object::restaurants_value;
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Restaurants(Vec<Restaurant>);
+mod phone;
+mod season;
+mod source;
+mod special;
+mod stale;
+mod times;
+pub use check::{Problem, check};
+pub use diff::{Change, Diff};
+pub use filter::{FILTER_INDEX_FILE, FilterIndex};
+pub use functions::add_template_functions;
+pub use now::parse_datetime;
+pub use object::{restaurant_values, restaurants_value};
+pub use phone::Phone;
+use season::Season;
+use special::Special;
+pub use stale::StaleReport;
+pub use times::TimesFormat;
+
+/// Filter slot size of the original hour based filter, see `RenderOptions::slot_minutes`.
+pub const HOUR_SLOT: u16 = 60;
+
+/// Listings verified longer ago than this are flagged as stale in templates.
+const STALE_AFTER_DAYS: i64 = 180;
+
+/// Restaurants as read, phone numbers are text until parsed with `Restaurants::parse_phones`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Restaurants<P = Phone>(Vec<Restaurant<P>>);
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
-struct Restaurant {
+struct Restaurant<P = Phone> {
     name: String,
     url: String,
-    phone: Option<String>,
+    phone: Option<P>,
     map_id: String, // Use with https://maps.app.goo.gl/{map_id}
     instagram_id: String,
     verified: NaiveDate,
+    /// Regular business hours, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    open_hours: Option<HappyTimes>,
     kind: Kind,
 }
 
@@ -28,13 +62,21 @@ enum Kind {
         description: Vec<String>,
         menu_url: Option<String>,
         happytimes: HappyTimes,
+        /// Deals on particular days, in addition to the `description`
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        specials: Vec<Special>,
+        /// Only during part of the year, e.g. `Some(Summer)`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        season: Option<Season>,
     },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct HappyTimes(Vec<DayHours>);
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// Written either as in `Display`, e.g. "Mon-Fri 4pm-6pm", or as e.g. `Range((Mon, Fri), (1600, 1800))`.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(try_from = "times::DayHoursRepr")]
 enum DayHours {
     Single(Day, Hours),
     Range((Day, Day), Hours),
@@ -52,41 +94,161 @@ enum Day {
 }
 
 #[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[serde(try_from = "times::HoursRepr")]
 struct Hours(Hour, Hour);
 
 #[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct Hour(#[serde(deserialize_with = "deserialize_hour")] u16);
+#[serde(try_from = "u16")]
+struct Hour(u16);
+
+/// Options affecting the values exposed to templates.
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    /// Date the site is built, used to compute the age of listings
+    pub today: NaiveDate,
+    pub stale_after_days: i64,
+    /// Whether per-restaurant iCalendar feeds are generated
+    pub calendars: bool,
+    /// Region assumed for phone numbers without a country code
+    pub phone_region: phonenumber::country::Id,
+    /// Whether the Atom feed of listing changes is generated
+    pub feed: bool,
+    /// Granularity of the day/time filter, 60 filters by whole hours
+    pub slot_minutes: u16,
+    /// Whether listings are filtered with `filter-index.json` rather than `data-daytimes` tokens
+    pub filter_index: bool,
+}
 
 struct HumanTime {
     description: String,
     data_attributes: String,
 }
 
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            today: chrono::Local::now().date_naive(),
+            stale_after_days: STALE_AFTER_DAYS,
+            calendars: false,
+            phone_region: phonenumber::country::US,
+            feed: false,
+            slot_minutes: HOUR_SLOT,
+            filter_index: false,
+        }
+    }
+}
+
+impl Restaurants {
+    /// Slug and verified date of every restaurant.
+    pub fn verified_dates(&self) -> Vec<(String, NaiveDate)> {
+        self.0
+            .iter()
+            .map(|restaurant| (restaurant.slug(), restaurant.verified))
+            .collect()
+    }
+
+    /// Start minutes of the filter slots from the earliest happy hour or special start
+    /// to the latest end.
+    fn slot_range(&self, slot_minutes: u16) -> StepBy<Range<u16>> {
+        let hours = self.0.iter().flat_map(|restaurant| match &restaurant.kind {
+            Kind::HappyHour {
+                happytimes,
+                specials,
+                ..
+            } => happytimes
+                .0
+                .iter()
+                .map(DayHours::hours)
+                .chain(specials.iter().filter_map(|special| special.hours.as_ref()))
+                .collect(),
+            _ => Vec::new(),
+        });
+        let (start, end) = hours.fold((u16::MAX, 0), |(start, end), hours| {
+            (
+                start.min(hours.0.minutes_of_day()),
+                end.max(hours.1.minutes_of_day()),
+            )
+        });
+        (start.min(end) / slot_minutes * slot_minutes..end).step_by(slot_minutes as usize)
+    }
+}
+
+/// Restaurants from RON source, with phone numbers parsed as local to the US.
+#[cfg(test)]
+fn parse_restaurants(source: &str) -> Restaurants {
+    crate::ron_options()
+        .from_str::<Restaurants<String>>(source)
+        .unwrap()
+        .parse_phones(phonenumber::country::US)
+        .unwrap()
+}
+
+impl<P> Restaurant<P> {
+    /// URL friendly identifier derived from the name, e.g. "deal-lake-bar-co".
+    fn slug(&self) -> String {
+        slugify(&self.name)
+    }
+
+    fn days_since_verified(&self, today: NaiveDate) -> i64 {
+        (today - self.verified).num_days()
+    }
+}
+
+impl Kind {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::HappyHour { .. } => "HappyHour",
+            Self::Byob => "Byob",
+            Self::Other => "Other",
+            Self::Closed => "Closed",
+        }
+    }
+
+    /// Whether a happy hour runs on `date` given its season, always true for other kinds.
+    fn in_season(&self, date: NaiveDate) -> bool {
+        match self {
+            Self::HappyHour {
+                season: Some(season),
+                ..
+            } => season.contains(date),
+            _ => true,
+        }
+    }
+}
+
 impl HappyTimes {
-    fn as_data_attributes(&self) -> String {
+    fn descriptions(&self) -> Vec<String> {
+        self.0.iter().map(|dh| dh.to_string()).collect()
+    }
+
+    fn as_data_attributes(&self, slot_minutes: u16) -> String {
         let dayhour_set = self
             .0
             .iter()
-            .flat_map(|dh| dh.as_tuples())
+            .flat_map(|dh| dh.as_tuples(slot_minutes))
             .collect::<BTreeSet<_>>();
 
         format!(
             r#"data-daytimes="{}""#,
-            Self::data_daytimes(dayhour_set.iter().copied())
+            Self::data_daytimes(dayhour_set.iter().copied(), slot_minutes)
         )
     }
 
-    fn as_human_readable(&self) -> Vec<HumanTime> {
+    fn as_human_readable(&self, slot_minutes: u16) -> Vec<HumanTime> {
         self.0
             .iter()
             .map(|dh| HumanTime {
                 description: format!("{}", dh),
-                data_attributes: dh.as_data_attributes(),
+                data_attributes: dh.as_data_attributes(slot_minutes),
             })
             .collect::<Vec<_>>()
     }
 
-    fn data_daytimes(dayhour_tuples: impl Iterator<Item = (Day, u16)>) -> String {
+    /// Filter tokens `day-slot`, `day-all` and `all-slot` for each (day, slot start minute).
+    fn data_daytimes(
+        dayhour_tuples: impl Iterator<Item = (Day, u16)>,
+        slot_minutes: u16,
+    ) -> String {
         let dayhour_tuples: Vec<_> = dayhour_tuples.collect();
         let days = dayhour_tuples
             .iter()
@@ -98,14 +260,39 @@ impl HappyTimes {
             .collect::<BTreeSet<_>>();
         dayhour_tuples
             .iter()
-            .map(|(d, h)| format!("{}-{h}", *d as isize))
+            .map(|(d, m)| format!("{}-{}", *d as isize, slot_token(*m, slot_minutes)))
             .chain(days.into_iter().map(|d| format!("{}-all", *d as isize)))
-            .chain(hours.into_iter().map(|h| format!("all-{h}")))
+            .chain(
+                hours
+                    .into_iter()
+                    .map(|m| format!("all-{}", slot_token(*m, slot_minutes))),
+            )
             .collect::<Vec<_>>()
             .join(" ")
     }
 }
 
+/// Filter token for the slot starting `minutes` after midnight,
+/// the hour (e.g. "16") in hour mode, otherwise the time as stored in the data (e.g. "1630").
+fn slot_token(minutes: u16, slot_minutes: u16) -> String {
+    if slot_minutes == HOUR_SLOT {
+        (minutes / 60).to_string()
+    } else {
+        (minutes / 60 * 100 + minutes % 60).to_string()
+    }
+}
+
+fn slugify(s: &str) -> String {
+    s.chars()
+        .filter(|c| *c != '\'')
+        .map(|c| c.to_ascii_lowercase())
+        .collect::<String>()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
 fn iter_days(days: (Day, Day)) -> impl Iterator<Item = Day> {
     let start = days.0 as usize;
     let end = days.1 as usize;
@@ -116,43 +303,30 @@ fn iter_days(days: (Day, Day)) -> impl Iterator<Item = Day> {
     Day::iter().cycle().skip(start).take(len)
 }
 
-fn deserialize_hour<'de, D>(deserializer: D) -> Result<u16, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let h = Hour(u16::deserialize(deserializer)?);
-    let ch = h.wraparound();
-    let hours = h.hours();
-    let minutes = ch.minutes();
-
-    // Validate minutes and hours
-    if hours > Hours::END_HOUR || minutes > 59 {
-        return Err(serde::de::Error::invalid_value(
-            serde::de::Unexpected::Unsigned(h.0 as u64),
-            &format!(
-                "an hour/minutes between {} and {} with minutes between 0 and 59",
-                Hours::START_HOUR * 100,
-                Hours::END_HOUR * 100
-            )
-            .as_str(),
-        ));
+impl DayHours {
+    fn days(&self) -> (Day, Day) {
+        match self {
+            DayHours::Single(day, _) => (*day, *day),
+            DayHours::Range(days, _) => *days,
+        }
     }
-    Ok(h.0)
-}
 
-impl DayHours {
-    fn as_tuples(&self) -> impl Iterator<Item = (Day, u16)> {
-        let (days, hours) = match self {
-            DayHours::Single(day, hours) => ((*day, *day), hours),
-            DayHours::Range(days, hours) => (*days, hours),
-        };
-        iter_days(days).flat_map(|day| hours.as_range().map(move |h| (day, h)))
+    fn hours(&self) -> &Hours {
+        match self {
+            DayHours::Single(_, hours) | DayHours::Range(_, hours) => hours,
+        }
+    }
+
+    /// Each day and start minute of the filter slots that start during these hours.
+    fn as_tuples(&self, slot_minutes: u16) -> impl Iterator<Item = (Day, u16)> {
+        let slots = self.hours().slots(slot_minutes);
+        iter_days(self.days()).flat_map(move |day| slots.clone().map(move |m| (day, m)))
     }
 
-    fn as_data_attributes(&self) -> String {
+    fn as_data_attributes(&self, slot_minutes: u16) -> String {
         format!(
             r#"data-daytimes="{}""#,
-            HappyTimes::data_daytimes(self.as_tuples()),
+            HappyTimes::data_daytimes(self.as_tuples(slot_minutes), slot_minutes),
         )
     }
 }
@@ -179,6 +353,10 @@ impl Day {
         ]
         .into_iter()
     }
+
+    fn from_index(index: usize) -> Day {
+        Day::iter().nth(index % 7).expect("day index")
+    }
 }
 
 impl Display for Day {
@@ -208,6 +386,11 @@ impl Hour {
     fn hours(&self) -> u16 {
         self.0 / 100
     }
+
+    /// Minutes past midnight, past 1440 for times after midnight.
+    fn minutes_of_day(&self) -> u16 {
+        self.hours() * 60 + self.minutes()
+    }
     fn minutes(&self) -> u16 {
         self.0 % 100
     }
@@ -238,22 +421,13 @@ impl Display for Hour {
 }
 
 impl Hours {
-    const START_HOUR: u16 = 9;
     const END_HOUR: u16 = 25;
 
-    fn as_range(&self) -> Range<u16> {
-        // Truncate to nearest hour, e.g. 1630 -> 16
-        let mut start = self.0.hours();
-        let mut end = self.1.hours();
-        // If start has minutes, bump to next hour
-        if self.0.minutes() > 0 {
-            start += 1;
-        }
-        // If end has minutes, bump to next hour
-        if self.1.minutes() > 0 {
-            end += 1;
-        }
-        start..end
+    /// Start minutes of the slots that begin during these hours,
+    /// e.g. 1630-1830 is 17:00 and 18:00 in hour slots, 16:30 to 18:00 in 30 minute slots.
+    fn slots(&self, slot_minutes: u16) -> StepBy<Range<u16>> {
+        let start = self.0.minutes_of_day().div_ceil(slot_minutes) * slot_minutes;
+        (start..self.1.minutes_of_day()).step_by(slot_minutes as usize)
     }
 }
 
@@ -277,7 +451,7 @@ mod tests {
                 Day::Wed,
                 Hours(Hour(1300), Hour(1500))
             )])
-            .as_data_attributes(),
+            .as_data_attributes(HOUR_SLOT),
             r#"data-daytimes="3-13 3-14 3-all all-13 all-14""#
         );
         assert_eq!(
@@ -285,7 +459,7 @@ mod tests {
                 DayHours::Single(Day::Wed, Hours(Hour(1300), Hour(1500))),
                 DayHours::Range((Day::Wed, Day::Fri), Hours(Hour(1400), Hour(1600))),
             ])
-            .as_data_attributes(),
+            .as_data_attributes(HOUR_SLOT),
             r#"data-daytimes="3-13 3-14 3-15 4-14 4-15 5-14 5-15 3-all 4-all 5-all all-13 all-14 all-15""#
         );
         assert_eq!(
@@ -293,7 +467,7 @@ mod tests {
                 Day::Mon,
                 Hours(Hour(1330), Hour(1530))
             )])
-            .as_data_attributes(),
+            .as_data_attributes(HOUR_SLOT),
             r#"data-daytimes="1-14 1-15 1-all all-14 all-15""#
         );
         assert_eq!(
@@ -301,7 +475,7 @@ mod tests {
                 (Day::Sat, Day::Sun),
                 Hours(Hour(1000), Hour(1200))
             )])
-            .as_data_attributes(),
+            .as_data_attributes(HOUR_SLOT),
             r#"data-daytimes="0-10 0-11 6-10 6-11 0-all 6-all all-10 all-11""#
         );
     }
@@ -309,16 +483,28 @@ mod tests {
     #[test]
     fn test_dayhours_dataattr() {
         assert_eq!(
-            DayHours::Single(Day::Wed, Hours(Hour(1300), Hour(1500))).as_data_attributes(),
+            DayHours::Single(Day::Wed, Hours(Hour(1300), Hour(1500))).as_data_attributes(HOUR_SLOT),
             r#"data-daytimes="3-13 3-14 3-all all-13 all-14""#
         );
         assert_eq!(
             DayHours::Range((Day::Wed, Day::Fri), Hours(Hour(1300), Hour(1700)))
-                .as_data_attributes(),
+                .as_data_attributes(HOUR_SLOT),
             r#"data-daytimes="3-13 3-14 3-15 3-16 4-13 4-14 4-15 4-16 5-13 5-14 5-15 5-16 3-all 4-all 5-all all-13 all-14 all-15 all-16""#
         );
     }
 
+    #[test]
+    fn test_dayhours_dataattr_slots() {
+        assert_eq!(
+            DayHours::Single(Day::Wed, Hours(Hour(1630), Hour(1800))).as_data_attributes(30),
+            r#"data-daytimes="3-1630 3-1700 3-1730 3-all all-1630 all-1700 all-1730""#
+        );
+        assert_eq!(
+            DayHours::Single(Day::Sat, Hours(Hour(2320), Hour(2500))).as_data_attributes(15),
+            r#"data-daytimes="6-2330 6-2345 6-2400 6-2415 6-2430 6-2445 6-all all-2330 all-2345 all-2400 all-2415 all-2430 all-2445""#
+        );
+    }
+
     #[test]
     fn test_dayrange() {
         assert_eq!(
@@ -339,6 +525,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Deal Lake Bar + Co"), "deal-lake-bar-co");
+        assert_eq!(slugify("Ada's Gojjo"), "adas-gojjo");
+        assert_eq!(slugify("  The Black Swan!"), "the-black-swan");
+    }
+
     #[test]
     fn test_hours_display() {
         assert_eq!("9:30am-2pm", format!("{}", Hours(Hour(930), Hour(1400))));
@@ -367,13 +560,14 @@ mod tests {
 
     #[test]
     fn test_save() {
-        let restaurants = Restaurants(vec![Restaurant {
+        let restaurants: Restaurants = Restaurants(vec![Restaurant {
             name: "The Black Swan".into(),
             url: "https://www.theblackswanap.com/".into(),
             phone: None,
             map_id: "JiKYhYvKsK2ysBZs9".into(),
             instagram_id: "theblackswanap".into(),
             verified: NaiveDate::from_ymd_opt(2025, 7, 28).unwrap(),
+            open_hours: None,
             kind: Kind::HappyHour {
                 description: vec![
                     "50% off all alcohol, HH food menu".into(),
@@ -385,6 +579,8 @@ mod tests {
                     DayHours::Single(Day::Tue, Hours(Hour(1600), Hour(2200))),
                     DayHours::Range((Day::Wed, Day::Fri), Hours(Hour(1600), Hour(1800))),
                 ]),
+                specials: Vec::new(),
+                season: None,
             },
         }]);
         let pretty = PrettyConfig::new()
@@ -0,0 +1,395 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Offset, TimeZone};
+use chrono_tz::{OffsetName, Tz};
+
+use super::{Day, DayHours, Kind, Restaurant, Restaurants, iter_days};
+
+/// Lines longer than this many octets are folded.
+const MAX_LINE: usize = 75;
+
+impl Restaurants {
+    /// A combined iCalendar feed of every happy hour, recurring weekly from `today`.
+    pub fn calendar(&self, today: NaiveDate, tz: Tz) -> String {
+        let mut ics = Ics::begin("Happy Hours", today, tz);
+        for restaurant in &self.0 {
+            ics.events(restaurant, today, tz);
+        }
+        ics.end()
+    }
+
+    /// One iCalendar feed per happy hour restaurant, paired with the restaurant slug.
+    pub fn restaurant_calendars(&self, today: NaiveDate, tz: Tz) -> Vec<(String, String)> {
+        self.0
+            .iter()
+            .filter(|restaurant| matches!(restaurant.kind, Kind::HappyHour { .. }))
+            .map(|restaurant| {
+                let mut ics = Ics::begin(&format!("{} Happy Hour", restaurant.name), today, tz);
+                ics.events(restaurant, today, tz);
+                (restaurant.slug(), ics.end())
+            })
+            .collect()
+    }
+}
+
+struct Ics(String);
+
+impl Ics {
+    fn begin(name: &str, today: NaiveDate, tz: Tz) -> Self {
+        let mut ics = Self(String::new());
+        ics.line("BEGIN:VCALENDAR");
+        ics.line("VERSION:2.0");
+        ics.line("PRODID:-//sitegen//Happy Hours//EN");
+        ics.line("CALSCALE:GREGORIAN");
+        ics.text("X-WR-CALNAME", name);
+        ics.line(&format!("X-WR-TIMEZONE:{}", tz.name()));
+        ics.timezone(today.year(), tz);
+        ics
+    }
+
+    fn end(mut self) -> String {
+        self.line("END:VCALENDAR");
+        self.0
+    }
+
+    fn events(&mut self, restaurant: &Restaurant, today: NaiveDate, tz: Tz) {
+        let Kind::HappyHour {
+            description,
+            menu_url,
+            happytimes,
+            specials,
+            season,
+        } = &restaurant.kind
+        else {
+            return;
+        };
+        // Seasonal happy hours recur from the start of this or the next season until its end
+        let (from, until) = match season {
+            Some(season) if season.is_over(today) => return,
+            Some(season) => season.dates(today),
+            None => (None, None),
+        };
+        let mut details = description.join("\n");
+        for special in specials {
+            details.push_str(&format!("\n{special}"));
+        }
+        if let Some(menu_url) = menu_url {
+            details.push_str(&format!("\nMenu: {menu_url}"));
+        }
+        if let Some(season) = season {
+            details.push_str(&format!("\n{season}"));
+        }
+        let mut until_rule = String::new();
+        if let Some(until) = until {
+            // UNTIL is in UTC when DTSTART has a TZID
+            let end_of_day = until.and_hms_opt(23, 59, 59).unwrap();
+            if let Some(utc) = tz.from_local_datetime(&end_of_day).latest() {
+                until_rule = format!(";UNTIL={}Z", ical_datetime(utc.naive_utc()));
+            }
+        }
+        for (index, dayhours) in happytimes.0.iter().enumerate() {
+            let (start, end) = first_occurrence(dayhours, from.unwrap_or(today).max(today));
+            let byday = iter_days(dayhours.days())
+                .map(|day| day.ical())
+                .collect::<Vec<_>>()
+                .join(",");
+            self.line("BEGIN:VEVENT");
+            self.line(&format!("UID:{}-{index}@sitegen", restaurant.slug()));
+            self.line(&format!("DTSTAMP:{}T000000Z", today.format("%Y%m%d")));
+            self.line(&format!(
+                "DTSTART;TZID={}:{}",
+                tz.name(),
+                ical_datetime(start)
+            ));
+            self.line(&format!("DTEND;TZID={}:{}", tz.name(), ical_datetime(end)));
+            self.line(&format!("RRULE:FREQ=WEEKLY;BYDAY={byday}{until_rule}"));
+            self.text("SUMMARY", &format!("Happy Hour: {}", restaurant.name));
+            self.text("DESCRIPTION", &details);
+            self.text("URL", menu_url.as_ref().unwrap_or(&restaurant.url));
+            self.line("END:VEVENT");
+        }
+    }
+
+    /// Describe the daylight saving rules of `tz` using its transitions in `year`.
+    fn timezone(&mut self, year: i32, tz: Tz) {
+        self.line("BEGIN:VTIMEZONE");
+        self.line(&format!("TZID:{}", tz.name()));
+        let transitions = transitions(year, tz);
+        if transitions.is_empty() {
+            let offset = tz.offset_from_utc_date(&NaiveDate::from_ymd_opt(year, 1, 1).unwrap());
+            self.line("BEGIN:STANDARD");
+            self.line(&format!("DTSTART:{year}0101T000000"));
+            let utc_offset = ical_offset(offset.fix().local_minus_utc());
+            self.line(&format!("TZOFFSETFROM:{utc_offset}"));
+            self.line(&format!("TZOFFSETTO:{utc_offset}"));
+            if let Some(abbreviation) = offset.abbreviation() {
+                self.line(&format!("TZNAME:{abbreviation}"));
+            }
+            self.line("END:STANDARD");
+        }
+        for transition in transitions {
+            let component = if transition.to > transition.from {
+                "DAYLIGHT"
+            } else {
+                "STANDARD"
+            };
+            let date = transition.local.date();
+            let days_in_month =
+                (date.with_day(1).unwrap() + chrono::Months::new(1) - Duration::days(1)).day();
+            let week = if date.day() + 7 > days_in_month {
+                -1
+            } else {
+                (date.day() as i32 - 1) / 7 + 1
+            };
+            self.line(&format!("BEGIN:{component}"));
+            self.line(&format!("DTSTART:{}", ical_datetime(transition.local)));
+            self.line(&format!(
+                "RRULE:FREQ=YEARLY;BYMONTH={};BYDAY={week}{}",
+                date.month(),
+                Day::from(date.weekday()).ical()
+            ));
+            self.line(&format!("TZOFFSETFROM:{}", ical_offset(transition.from)));
+            self.line(&format!("TZOFFSETTO:{}", ical_offset(transition.to)));
+            if let Some(abbreviation) = &transition.abbreviation {
+                self.line(&format!("TZNAME:{abbreviation}"));
+            }
+            self.line(&format!("END:{component}"));
+        }
+        self.line("END:VTIMEZONE");
+    }
+
+    fn text(&mut self, name: &str, value: &str) {
+        let escaped = value
+            .replace('\\', "\\\\")
+            .replace(';', "\\;")
+            .replace(',', "\\,")
+            .replace('\n', "\\n");
+        self.line(&format!("{name}:{escaped}"));
+    }
+
+    /// Append a content line, folding it to the maximum line length.
+    fn line(&mut self, line: &str) {
+        let mut width = 0;
+        for c in line.chars() {
+            let len = c.len_utf8();
+            if width + len > MAX_LINE {
+                self.0.push_str("\r\n ");
+                width = 1;
+            }
+            self.0.push(c);
+            width += len;
+        }
+        self.0.push_str("\r\n");
+    }
+}
+
+/// A change in UTC offset, with the local time it happens at before the change.
+struct Transition {
+    local: NaiveDateTime,
+    from: i32,
+    to: i32,
+    abbreviation: Option<String>,
+}
+
+fn transitions(year: i32, tz: Tz) -> Vec<Transition> {
+    let start = NaiveDate::from_ymd_opt(year, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let offset = |utc: NaiveDateTime| tz.offset_from_utc_datetime(&utc);
+    let mut transitions = Vec::new();
+    let mut previous = offset(start).fix().local_minus_utc();
+    // Offsets change on the hour in practice
+    for hour in 1..=(366 * 24) {
+        let utc = start + Duration::hours(hour);
+        if utc.year() != year {
+            break;
+        }
+        let current = offset(utc);
+        let seconds = current.fix().local_minus_utc();
+        if seconds != previous {
+            transitions.push(Transition {
+                local: utc + Duration::seconds(previous as i64),
+                from: previous,
+                to: seconds,
+                abbreviation: current.abbreviation().map(String::from),
+            });
+            previous = seconds;
+        }
+    }
+    transitions
+}
+
+/// The first start and end at or after `today`, the end may fall on the following day.
+fn first_occurrence(dayhours: &DayHours, today: NaiveDate) -> (NaiveDateTime, NaiveDateTime) {
+    let days: Vec<Day> = iter_days(dayhours.days()).collect();
+    let date = today
+        .iter_days()
+        .find(|date| days.contains(&Day::from(date.weekday())))
+        .expect("date");
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+    let minutes =
+        |hour: super::Hour| Duration::minutes((hour.hours() * 60 + hour.minutes()) as i64);
+    let hours = dayhours.hours();
+    (midnight + minutes(hours.0), midnight + minutes(hours.1))
+}
+
+fn ical_datetime(datetime: NaiveDateTime) -> String {
+    datetime.format("%Y%m%dT%H%M%S").to_string()
+}
+
+fn ical_offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    format!("{sign}{:02}{:02}", minutes / 60, minutes % 60)
+}
+
+impl Day {
+    fn ical(&self) -> &'static str {
+        match self {
+            Self::Sun => "SU",
+            Self::Mon => "MO",
+            Self::Tue => "TU",
+            Self::Wed => "WE",
+            Self::Thu => "TH",
+            Self::Fri => "FR",
+            Self::Sat => "SA",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::parse_restaurants;
+    use chrono_tz::America::New_York;
+
+    fn restaurants() -> Restaurants {
+        parse_restaurants(
+            r#"[
+    (
+        name: "The Black Swan",
+        url: "https://www.theblackswanap.com/",
+        phone: None,
+        map_id: "JiKYhYvKsK2ysBZs9",
+        instagram_id: "theblackswanap",
+        verified: "2025-07-28",
+        kind: HappyHour(
+            description: [
+                "50% off all alcohol, HH food menu",
+                "Wed 2nd burger $5",
+            ],
+            menu_url: Some("https://www.theblackswanap.com/happy-hour"),
+            happytimes: [
+                Range((Wed, Fri), (1600, 1800)),
+                Single(Fri, (2300, 2500)),
+            ],
+        ),
+    ),
+    (
+        name: "Ada's Gojjo",
+        url: "https://adagojjo.com/",
+        phone: None,
+        map_id: "nJi3bs3Sy1EGjbgX7",
+        instagram_id: "adagojjo",
+        verified: "2024-04-16",
+        kind: Byob,
+    ),
+]"#,
+        )
+    }
+
+    #[test]
+    fn test_restaurant_calendars() {
+        // A Monday
+        let today = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
+        let calendars = restaurants().restaurant_calendars(today, New_York);
+        assert_eq!(calendars.len(), 1);
+        let (slug, ics) = &calendars[0];
+        assert_eq!(slug, "the-black-swan");
+        assert_eq!(
+            ics.split("BEGIN:VEVENT").nth(1).unwrap(),
+            "\r
+UID:the-black-swan-0@sitegen\r
+DTSTAMP:20250901T000000Z\r
+DTSTART;TZID=America/New_York:20250903T160000\r
+DTEND;TZID=America/New_York:20250903T180000\r
+RRULE:FREQ=WEEKLY;BYDAY=WE,TH,FR\r
+SUMMARY:Happy Hour: The Black Swan\r
+DESCRIPTION:50% off all alcohol\\, HH food menu\\nWed 2nd burger $5\\nMenu: ht\r
+ tps://www.theblackswanap.com/happy-hour\r
+URL:https://www.theblackswanap.com/happy-hour\r
+END:VEVENT\r
+"
+        );
+        // Crosses midnight into Saturday
+        assert!(ics.contains(
+            "DTSTART;TZID=America/New_York:20250905T230000\r
+DTEND;TZID=America/New_York:20250906T010000\r
+RRULE:FREQ=WEEKLY;BYDAY=FR\r"
+        ));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn test_seasonal_calendar() {
+        let restaurants = parse_restaurants(
+            r#"[
+    (
+        name: "Beach Bar",
+        url: "https://example.com/",
+        phone: None,
+        map_id: "a",
+        instagram_id: "",
+        verified: "2025-07-28",
+        kind: HappyHour(
+            description: ["$5 drafts"],
+            menu_url: None,
+            happytimes: [Single(Fri, (1600, 1800))],
+            season: Some(Summer),
+        ),
+    ),
+]"#,
+        );
+        // After Labor Day, so from next Memorial Day until the next Labor Day
+        let today = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+        let ics = restaurants.calendar(today, New_York);
+        assert!(ics.contains(
+            "DTSTART;TZID=America/New_York:20260529T160000\r
+DTEND;TZID=America/New_York:20260529T180000\r
+RRULE:FREQ=WEEKLY;BYDAY=FR;UNTIL=20260908T035959Z\r"
+        ));
+        assert!(ics.contains("DESCRIPTION:$5 drafts\\nMemorial Day to Labor Day\r"));
+    }
+
+    #[test]
+    fn test_vtimezone() {
+        let today = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
+        let ics = restaurants().calendar(today, New_York);
+        let start = ics.find("BEGIN:VTIMEZONE").unwrap();
+        let end = ics.find("END:VTIMEZONE").unwrap();
+        assert_eq!(
+            &ics[start..end],
+            "BEGIN:VTIMEZONE\r
+TZID:America/New_York\r
+BEGIN:DAYLIGHT\r
+DTSTART:20250309T020000\r
+RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU\r
+TZOFFSETFROM:-0500\r
+TZOFFSETTO:-0400\r
+TZNAME:EDT\r
+END:DAYLIGHT\r
+BEGIN:STANDARD\r
+DTSTART:20251102T020000\r
+RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU\r
+TZOFFSETFROM:-0400\r
+TZOFFSETTO:-0500\r
+TZNAME:EST\r
+END:STANDARD\r
+"
+        );
+        assert!(
+            restaurants()
+                .calendar(today, chrono_tz::Europe::London)
+                .contains("RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU\r\n")
+        );
+    }
+}
@@ -0,0 +1,554 @@
+use std::{collections::HashMap, fmt::Display};
+
+use chrono::NaiveDate;
+use ron::error::Position;
+
+use super::{
+    DayHours, Hours, Kind, Phone, Restaurant, Restaurants, Season, iter_days, source::SourceNode,
+    times::lenient_hours,
+};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+const MINUTES_PER_WEEK: u32 = 7 * MINUTES_PER_DAY;
+
+/// A problem found in a restaurants data file.
+#[derive(Debug, PartialEq)]
+pub struct Problem {
+    pub position: Option<Position>,
+    pub message: String,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{position}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Validate RON restaurant data, reporting every problem found.
+/// Dates after `today` are reported as unverifiable, phone numbers are parsed for `phone_region`.
+pub fn check(
+    source: &str,
+    today: NaiveDate,
+    phone_region: phonenumber::country::Id,
+) -> Vec<Problem> {
+    // Invalid hours are reported below along with everything else
+    let restaurants: Restaurants<String> =
+        match lenient_hours(|| crate::ron_options().from_str(source)) {
+            Ok(restaurants) => restaurants,
+            Err(err) => {
+                return vec![Problem {
+                    position: Some(err.span.start),
+                    message: err.code.to_string(),
+                }];
+            }
+        };
+    let root = SourceNode::parse(source);
+
+    let mut checker = Checker {
+        problems: Vec::new(),
+        today,
+        phone_region,
+    };
+    let mut names = HashMap::new();
+    let mut map_ids = HashMap::new();
+    for (index, restaurant) in restaurants.0.iter().enumerate() {
+        let node = root.item(index);
+        if let Some(other) = names.insert(&restaurant.name, index) {
+            checker.report(
+                node.and_then(|n| n.field("name")).or(node),
+                restaurant,
+                format!("duplicate name, also used by entry {}", other + 1),
+            );
+        }
+        if let Some(other) = map_ids.insert(&restaurant.map_id, index) {
+            checker.report(
+                node.and_then(|n| n.field("map_id")).or(node),
+                restaurant,
+                format!(
+                    "duplicate map_id, also used by {:?}",
+                    restaurants.0[other].name
+                ),
+            );
+        }
+        checker.restaurant(restaurant, node);
+    }
+    checker.problems
+}
+
+struct Checker {
+    problems: Vec<Problem>,
+    today: NaiveDate,
+    phone_region: phonenumber::country::Id,
+}
+
+impl Checker {
+    fn report(
+        &mut self,
+        node: Option<&SourceNode>,
+        restaurant: &Restaurant<String>,
+        message: String,
+    ) {
+        self.problems.push(Problem {
+            position: node.map(|n| n.position),
+            message: format!("{}: {message}", restaurant.name),
+        });
+    }
+
+    fn restaurant(&mut self, restaurant: &Restaurant<String>, node: Option<&SourceNode>) {
+        let field = |name| node.and_then(|n| n.field(name)).or(node);
+
+        if let Some(phone) = &restaurant.phone
+            && let Err(message) = Phone::parse(phone, self.phone_region)
+        {
+            self.report(field("phone"), restaurant, message);
+        }
+        if let Err(message) = check_url(&restaurant.url) {
+            self.report(field("url"), restaurant, message);
+        }
+        if restaurant.verified > self.today {
+            self.report(
+                field("verified"),
+                restaurant,
+                format!("verified date {} is in the future", restaurant.verified),
+            );
+        }
+        // The list is inside Some(...)
+        let open_hours_node = field("open_hours");
+        let open_hours_node = open_hours_node.and_then(|n| n.item(0)).or(open_hours_node);
+        if let Some(open_hours) = &restaurant.open_hours {
+            for (index, dayhours) in open_hours.0.iter().enumerate() {
+                let entry_node = open_hours_node
+                    .and_then(|n| n.item(index))
+                    .or(open_hours_node);
+                self.check_hours(entry_node, restaurant, dayhours);
+            }
+        }
+
+        let Kind::HappyHour {
+            description,
+            menu_url,
+            happytimes,
+            specials,
+            season,
+        } = &restaurant.kind
+        else {
+            return;
+        };
+        let kind = field("kind");
+        let kind_field = |name| kind.and_then(|n| n.field(name)).or(kind);
+
+        if let Some(Season::Between(from, until)) = season
+            && until < from
+        {
+            self.report(
+                kind_field("season"),
+                restaurant,
+                format!("season ends {until} before it starts {from}"),
+            );
+        } else if let Some(season) = season
+            && season.is_over(self.today)
+        {
+            self.report(
+                kind_field("season"),
+                restaurant,
+                format!("season {season} is over"),
+            );
+        }
+
+        if description.is_empty() && specials.is_empty() {
+            self.report(
+                kind_field("description"),
+                restaurant,
+                "empty description".into(),
+            );
+        }
+        if let Some(menu_url) = menu_url
+            && let Err(message) = check_url(menu_url)
+        {
+            self.report(kind_field("menu_url"), restaurant, message);
+        }
+
+        let specials_node = kind_field("specials");
+        for (index, special) in specials.iter().enumerate() {
+            let special_node = specials_node.and_then(|n| n.item(index)).or(specials_node);
+            if let Some(hours) = &special.hours {
+                // The hours are inside Some(...)
+                let hours_node = special_node.and_then(|n| n.field("hours"));
+                let hours_node = hours_node.and_then(|n| n.item(0)).or(hours_node);
+                if !self.valid_hours(hours_node.or(special_node), restaurant, hours) {
+                    continue;
+                }
+            }
+            match special.hours {
+                Some(hours) if hours.1 <= hours.0 => self.report(
+                    special_node.and_then(|n| n.field("hours")).or(special_node),
+                    restaurant,
+                    format!(
+                        "{special}: end {} is not after start {}",
+                        hours.1.0, hours.0.0
+                    ),
+                ),
+                None if happytimes
+                    .0
+                    .iter()
+                    .all(|dh| !iter_days(dh.days()).any(|day| special.days.contains(day))) =>
+                {
+                    self.report(
+                        special_node,
+                        restaurant,
+                        format!(
+                            "{special}: no happy hour on {}, give it hours",
+                            special.days
+                        ),
+                    )
+                }
+                _ => {}
+            }
+        }
+
+        let happytimes_node = kind_field("happytimes");
+        let entry_node = |index: usize| {
+            happytimes_node
+                .and_then(|n| n.item(index))
+                .or(happytimes_node)
+        };
+        // Entries with invalid hours are only reported as such
+        let mut valid = Vec::new();
+        for (index, dayhours) in happytimes.0.iter().enumerate() {
+            if !self.check_hours(entry_node(index), restaurant, dayhours) {
+                continue;
+            }
+            valid.push((index, dayhours));
+            if let Some(open_hours) = &restaurant.open_hours
+                && !within(dayhours, &open_hours.0)
+            {
+                self.report(
+                    entry_node(index),
+                    restaurant,
+                    format!("{dayhours} is outside the open hours"),
+                );
+            }
+        }
+        for (position, &(index, dayhours)) in valid.iter().enumerate() {
+            for &(other_index, other) in &valid[position + 1..] {
+                if overlaps(dayhours, other) {
+                    self.report(
+                        entry_node(other_index),
+                        restaurant,
+                        format!("{other} overlaps {dayhours} (entry {})", index + 1),
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl Checker {
+    /// Report problems with the hours of `dayhours`, returning false if they are invalid hours.
+    fn check_hours(
+        &mut self,
+        node: Option<&SourceNode>,
+        restaurant: &Restaurant<String>,
+        dayhours: &DayHours,
+    ) -> bool {
+        let hours = dayhours.hours();
+        let hours_node = node.and_then(|n| n.item(1)).or(node);
+        if !self.valid_hours(hours_node, restaurant, hours) {
+            return false;
+        }
+        if hours.1 <= hours.0 {
+            self.report(
+                hours_node,
+                restaurant,
+                format!(
+                    "{dayhours}: end {} is not after start {}",
+                    hours.1.0, hours.0.0
+                ),
+            );
+        }
+        true
+    }
+
+    /// Report each invalid hour in `hours`, at the start or end in the hours `node`.
+    fn valid_hours(
+        &mut self,
+        node: Option<&SourceNode>,
+        restaurant: &Restaurant<String>,
+        hours: &Hours,
+    ) -> bool {
+        let mut valid = true;
+        for (index, hour) in [hours.0, hours.1].into_iter().enumerate() {
+            if let Err(message) = hour.validate() {
+                self.report(
+                    node.and_then(|n| n.item(index)).or(node),
+                    restaurant,
+                    message,
+                );
+                valid = false;
+            }
+        }
+        valid
+    }
+}
+
+pub(super) fn check_url(url: &str) -> Result<(), String> {
+    match url::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.host().is_some() => {
+            Ok(())
+        }
+        Ok(_) => Err(format!("invalid url {url:?}: not an http(s) url")),
+        Err(err) => Err(format!("invalid url {url:?}: {err}")),
+    }
+}
+
+/// Week-relative minute intervals covered by `dayhours`, hours past midnight extend into the next day.
+fn week_intervals(dayhours: &DayHours) -> impl Iterator<Item = (u32, u32)> {
+    let hours = *dayhours.hours();
+    iter_days(dayhours.days()).map(move |day| {
+        let start = day as u32 * MINUTES_PER_DAY + minutes(&hours, 0);
+        let end = day as u32 * MINUTES_PER_DAY + minutes(&hours, 1);
+        (start, end)
+    })
+}
+
+fn minutes(hours: &Hours, index: usize) -> u32 {
+    let hour = if index == 0 { hours.0 } else { hours.1 };
+    hour.hours() as u32 * 60 + hour.minutes() as u32
+}
+
+/// Whether every day and time of `dayhours` is during one of the `open` hours,
+/// hours that run on from one entry into the next count as one.
+fn within(dayhours: &DayHours, open: &[DayHours]) -> bool {
+    // Open hours repeated a week later, so Sat 2300-2500 and Sun 0000-0200 join up
+    let mut intervals: Vec<_> = open
+        .iter()
+        .flat_map(week_intervals)
+        .flat_map(|(start, end)| {
+            [
+                (start, end),
+                (start + MINUTES_PER_WEEK, end + MINUTES_PER_WEEK),
+            ]
+        })
+        .collect();
+    intervals.sort();
+    let mut merged: Vec<(u32, u32)> = Vec::new();
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    week_intervals(dayhours).all(|(start, end)| {
+        [0, MINUTES_PER_WEEK].iter().any(|&shift| {
+            merged.iter().any(|&(open_start, open_end)| {
+                open_start <= start + shift && end + shift <= open_end
+            })
+        })
+    })
+}
+
+fn overlaps(a: &DayHours, b: &DayHours) -> bool {
+    week_intervals(a).any(|(a_start, a_end)| {
+        week_intervals(b).any(|(b_start, b_end)| {
+            // Compare across the end of the week too, e.g. Sat 2300-2500 and Sun 0000-0100
+            [0, MINUTES_PER_WEEK].iter().any(|&shift| {
+                (a_start + shift < b_end && b_start < a_end + shift)
+                    || (b_start + shift < a_end && a_start < b_end + shift)
+            })
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 12, 31).unwrap()
+    }
+
+    #[test]
+    fn test_check_valid() {
+        let source = include_str!("../../tests/site/_data/restaurants.ron");
+        assert_eq!(check(source, today(), phonenumber::country::US), vec![]);
+    }
+
+    #[test]
+    fn test_check_problems() {
+        let source = r#"[
+    (
+        name: "The Black Swan",
+        url: "theblackswanap.com",
+        phone: Some("call us"),
+        map_id: "JiKYhYvKsK2ysBZs9",
+        instagram_id: "theblackswanap",
+        verified: "2026-01-05",
+        kind: HappyHour(
+            description: [],
+            menu_url: Some("https://www.theblackswanap.com/happy-hour"),
+            happytimes: [
+                Range((Mon, Fri), (1600, 1800)),
+                Single(Wed, (1700, 1600)),
+                Single(Fri, (1730, 1900)),
+                Single(Sat, (2300, 2500)),
+                Single(Sun, (0030, 0100)),
+            ],
+        ),
+    ),
+    (
+        name: "The Black Swan",
+        url: "https://www.theblackswanap.com/",
+        phone: None,
+        map_id: "JiKYhYvKsK2ysBZs9",
+        instagram_id: "theblackswanap",
+        verified: "2025-07-28",
+        kind: Byob,
+    ),
+]"#;
+        let problems: Vec<_> = check(source, today(), phonenumber::country::US)
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                r#"5:16: The Black Swan: invalid phone "call us": not a number"#,
+                r#"4:14: The Black Swan: invalid url "theblackswanap.com": relative URL without a base"#,
+                "8:19: The Black Swan: verified date 2026-01-05 is in the future",
+                "10:26: The Black Swan: empty description",
+                "14:29: The Black Swan: Wed 5pm-4pm: end 1600 is not after start 1700",
+                "15:17: The Black Swan: Fri 5:30pm-7pm overlaps Mon-Fri 4pm-6pm (entry 1)",
+                "17:17: The Black Swan: Sun 12:30am-1am overlaps Sat 11pm-1am (entry 4)",
+                "22:15: The Black Swan: duplicate name, also used by entry 1",
+                r#"25:17: The Black Swan: duplicate map_id, also used by "The Black Swan""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_invalid_hours() {
+        let source = r#"[
+    (
+        name: "Late Bar",
+        url: "late bar",
+        phone: None,
+        map_id: "a",
+        instagram_id: "a",
+        verified: "2025-07-28",
+        open_hours: Some([Range((Mon, Sun), (1200, 2700))]),
+        kind: HappyHour(
+            description: ["$5 drafts"],
+            menu_url: None,
+            happytimes: [
+                Single(Fri, (1660, 1800)),
+                Single(Sat, (1600, 1800)),
+                Single(Sat, (1700, 4900)),
+            ],
+            specials: [(days: Single(Sat), hours: Some((1500, 1575)), item: "Wings")],
+        ),
+    ),
+]"#;
+        let problems: Vec<_> = check(source, today(), phonenumber::country::US)
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                r#"4:14: Late Bar: invalid url "late bar": relative URL without a base"#,
+                "9:52: Late Bar: invalid hour 2700, expected e.g. 1630, up to 2500 with minutes between 0 and 59",
+                "18:63: Late Bar: invalid hour 1575, expected e.g. 1630, up to 2500 with minutes between 0 and 59",
+                "14:30: Late Bar: invalid hour 1660, expected e.g. 1630, up to 2500 with minutes between 0 and 59",
+                "16:36: Late Bar: invalid hour 4900, expected e.g. 1630, up to 2500 with minutes between 0 and 59",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_open_hours() {
+        let source = r#"[
+    (
+        name: "Late Bar",
+        url: "https://example.com/",
+        phone: None,
+        map_id: "a",
+        instagram_id: "a",
+        verified: "2025-07-28",
+        open_hours: Some([
+            Range((Mon, Fri), (1600, 2400)),
+            Range((Sat, Sun), (0000, 0200)),
+            Single(Sat, (1200, 2400)),
+        ]),
+        kind: HappyHour(
+            description: ["$5 drafts"],
+            menu_url: None,
+            happytimes: [
+                Range((Mon, Thu), (1600, 1800)),
+                Single(Fri, (2300, 2500)),
+                Single(Sat, (1100, 1300)),
+                Single(Sun, (1600, 1800)),
+                Single(Sat, (2330, 2500)),
+            ],
+        ),
+    ),
+]"#;
+        let problems: Vec<_> = check(source, today(), phonenumber::country::US)
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                "20:17: Late Bar: Sat 11am-1pm is outside the open hours",
+                "21:17: Late Bar: Sun 4pm-6pm is outside the open hours",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_season() {
+        let entry = |season| {
+            format!(
+                r#"[(
+    name: "Beach Bar",
+    url: "https://example.com/",
+    phone: None,
+    map_id: "a",
+    instagram_id: "",
+    verified: "2025-07-28",
+    kind: HappyHour(
+        description: ["$5 drafts"],
+        menu_url: None,
+        happytimes: [Single(Fri, (1600, 1800))],
+        season: Some({season}),
+    ),
+)]"#
+            )
+        };
+        let problems = |season| {
+            check(&entry(season), today(), phonenumber::country::US)
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert!(problems("Summer").is_empty());
+        assert_eq!(
+            problems(r#"Between("2025-09-01", "2025-06-01")"#),
+            vec!["12:17: Beach Bar: season ends 2025-06-01 before it starts 2025-09-01"]
+        );
+        assert_eq!(
+            problems(r#"Until("2025-09-01")"#),
+            vec!["12:17: Beach Bar: season until Sep 1, 2025 is over"]
+        );
+    }
+
+    #[test]
+    fn test_check_syntax_error() {
+        let problems = check("[(name: ]", today(), phonenumber::country::US);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].position.is_some());
+    }
+}
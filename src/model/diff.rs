@@ -0,0 +1,537 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use super::{HappyTimes, Kind, Restaurant, Restaurants, Season, Special};
+
+/// A notable change to a restaurant between two versions of the data.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum Change {
+    Added {
+        name: String,
+        slug: String,
+    },
+    Removed {
+        name: String,
+        slug: String,
+    },
+    Closed {
+        name: String,
+        slug: String,
+    },
+    /// Happy hours changed, `before` and `after` are human readable times
+    Retimed {
+        name: String,
+        slug: String,
+        before: Vec<String>,
+        after: Vec<String>,
+    },
+}
+
+impl Change {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Added { name, .. }
+            | Self::Removed { name, .. }
+            | Self::Closed { name, .. }
+            | Self::Retimed { name, .. } => name,
+        }
+    }
+
+    pub fn slug(&self) -> &str {
+        match self {
+            Self::Added { slug, .. }
+            | Self::Removed { slug, .. }
+            | Self::Closed { slug, .. }
+            | Self::Retimed { slug, .. } => slug,
+        }
+    }
+
+    /// Short identifier of the kind of change, e.g. "retimed".
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Added { .. } => "added",
+            Self::Removed { .. } => "removed",
+            Self::Closed { .. } => "closed",
+            Self::Retimed { .. } => "retimed",
+        }
+    }
+
+    /// Details of the change beyond its title, if any.
+    pub fn summary(&self) -> Option<String> {
+        match self {
+            Self::Retimed { before, after, .. } => Some(format!(
+                "{} → {}",
+                times_or_none(before),
+                times_or_none(after)
+            )),
+            _ => None,
+        }
+    }
+}
+
+fn times_or_none(times: &[String]) -> String {
+    if times.is_empty() {
+        "no happy hour".to_owned()
+    } else {
+        times.join(", ")
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Added { name, .. } => write!(f, "Added {name}"),
+            Self::Removed { name, .. } => write!(f, "Removed {name}"),
+            Self::Closed { name, .. } => write!(f, "{name} closed"),
+            Self::Retimed { name, .. } => write!(f, "New happy hours at {name}"),
+        }
+    }
+}
+
+/// Semantic differences between two versions of the restaurant data.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Diff(Vec<RestaurantDiff>);
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum RestaurantDiff {
+    Added {
+        name: String,
+    },
+    Removed {
+        name: String,
+    },
+    Changed {
+        name: String,
+        changes: Vec<FieldChange>,
+    },
+}
+
+/// A changed field, `None` where it was absent.
+#[derive(Serialize, Debug, PartialEq)]
+struct FieldChange {
+    field: &'static str,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+/// A restaurant matched with its previous version, `None` if it was added.
+type Matched<'a, P, Q> = (&'a Restaurant<P>, Option<&'a Restaurant<Q>>);
+
+impl<P> Restaurants<P> {
+    /// Restaurants added, removed, closed or re-timed since `previous`.
+    pub fn changes_since<Q>(&self, previous: &Restaurants<Q>) -> Vec<Change> {
+        let (matched, removed) = self.match_previous(previous);
+        let mut changes = Vec::new();
+        for (restaurant, previous) in matched {
+            match previous {
+                Some(previous) => changes.extend(restaurant.change_since(previous)),
+                None => changes.push(Change::Added {
+                    name: restaurant.name.clone(),
+                    slug: restaurant.slug(),
+                }),
+            }
+        }
+        for restaurant in removed {
+            changes.push(Change::Removed {
+                name: restaurant.name.clone(),
+                slug: restaurant.slug(),
+            });
+        }
+        changes
+    }
+
+    /// Pair each restaurant with its previous version, also returning those no longer present.
+    /// Restaurants are matched by name, or by `map_id` if they were renamed.
+    fn match_previous<'a, Q>(
+        &'a self,
+        previous: &'a Restaurants<Q>,
+    ) -> (Vec<Matched<'a, P, Q>>, Vec<&'a Restaurant<Q>>) {
+        let mut used = vec![false; previous.0.len()];
+        let by_name: Vec<_> = self
+            .0
+            .iter()
+            .map(|restaurant| {
+                let index = previous.0.iter().position(|p| p.name == restaurant.name);
+                if let Some(index) = index {
+                    used[index] = true;
+                }
+                index
+            })
+            .collect();
+        let matched = self
+            .0
+            .iter()
+            .zip(by_name)
+            .map(|(restaurant, index)| {
+                let index = index.or_else(|| {
+                    let index = previous
+                        .0
+                        .iter()
+                        .enumerate()
+                        .position(|(i, p)| !used[i] && p.map_id == restaurant.map_id)?;
+                    used[index] = true;
+                    Some(index)
+                });
+                (restaurant, index.map(|index| &previous.0[index]))
+            })
+            .collect();
+        let removed = previous
+            .0
+            .iter()
+            .zip(used)
+            .filter(|(_, used)| !used)
+            .map(|(restaurant, _)| restaurant)
+            .collect();
+        (matched, removed)
+    }
+}
+
+impl<P: AsRef<str>> Restaurants<P> {
+    /// Every field that differs from `previous`, restaurant by restaurant.
+    pub fn diff(&self, previous: &Self) -> Diff {
+        let (matched, removed) = self.match_previous(previous);
+        let mut diffs = Vec::new();
+        for (restaurant, previous) in matched {
+            match previous {
+                Some(previous) => {
+                    let changes = restaurant.field_changes(previous);
+                    if !changes.is_empty() {
+                        diffs.push(RestaurantDiff::Changed {
+                            name: restaurant.name.clone(),
+                            changes,
+                        });
+                    }
+                }
+                None => diffs.push(RestaurantDiff::Added {
+                    name: restaurant.name.clone(),
+                }),
+            }
+        }
+        for restaurant in removed {
+            diffs.push(RestaurantDiff::Removed {
+                name: restaurant.name.clone(),
+            });
+        }
+        Diff(diffs)
+    }
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |value: &Option<String>| value.clone().unwrap_or_else(|| "none".into());
+        for diff in &self.0 {
+            match diff {
+                RestaurantDiff::Added { name } => writeln!(f, "+ {name}")?,
+                RestaurantDiff::Removed { name } => writeln!(f, "- {name}")?,
+                RestaurantDiff::Changed { name, changes } => {
+                    writeln!(f, "~ {name}")?;
+                    for change in changes {
+                        writeln!(
+                            f,
+                            "    {}: {} → {}",
+                            change.field,
+                            show(&change.before),
+                            show(&change.after)
+                        )?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<P> Restaurant<P> {
+    fn change_since<Q>(&self, previous: &Restaurant<Q>) -> Option<Change> {
+        let name = self.name.clone();
+        let slug = self.slug();
+        if matches!(self.kind, Kind::Closed) {
+            return (!matches!(previous.kind, Kind::Closed))
+                .then_some(Change::Closed { name, slug });
+        }
+        let times = |kind: &Kind| match kind {
+            Kind::HappyHour { happytimes, .. } => happytimes.descriptions(),
+            _ => Vec::new(),
+        };
+        let (before, after) = (times(&previous.kind), times(&self.kind));
+        (before != after).then_some(Change::Retimed {
+            name,
+            slug,
+            before,
+            after,
+        })
+    }
+}
+
+impl<P: AsRef<str>> Restaurant<P> {
+    fn field_changes(&self, previous: &Self) -> Vec<FieldChange> {
+        let mut changes = Vec::new();
+        let mut field = |field, before: Option<String>, after: Option<String>| {
+            if before != after {
+                changes.push(FieldChange {
+                    field,
+                    before,
+                    after,
+                });
+            }
+        };
+        field("name", Some(previous.name.clone()), Some(self.name.clone()));
+        field("url", Some(previous.url.clone()), Some(self.url.clone()));
+        let phone = |restaurant: &Self| {
+            restaurant
+                .phone
+                .as_ref()
+                .map(|phone| phone.as_ref().to_owned())
+        };
+        field("phone", phone(previous), phone(self));
+        field(
+            "map_id",
+            Some(previous.map_id.clone()),
+            Some(self.map_id.clone()),
+        );
+        field(
+            "instagram_id",
+            Some(previous.instagram_id.clone()),
+            Some(self.instagram_id.clone()),
+        );
+        field(
+            "verified",
+            Some(previous.verified.to_string()),
+            Some(self.verified.to_string()),
+        );
+        let before = previous.open_hours.as_ref().map(HappyTimes::descriptions);
+        let after = self.open_hours.as_ref().map(HappyTimes::descriptions);
+        for (before, after) in paired_times(before.unwrap_or_default(), after.unwrap_or_default()) {
+            field("open_hours", before, after);
+        }
+        field(
+            "kind",
+            Some(previous.kind.name().to_owned()),
+            Some(self.kind.name().to_owned()),
+        );
+        let description = |kind: &Kind| match kind {
+            Kind::HappyHour { description, .. } => Some(description.join("; ")),
+            _ => None,
+        };
+        field(
+            "description",
+            description(&previous.kind),
+            description(&self.kind),
+        );
+        let menu_url = |kind: &Kind| match kind {
+            Kind::HappyHour { menu_url, .. } => menu_url.clone(),
+            _ => None,
+        };
+        field("menu_url", menu_url(&previous.kind), menu_url(&self.kind));
+        field(
+            "season",
+            previous.kind.season().map(Season::to_string),
+            self.kind.season().map(Season::to_string),
+        );
+
+        let times = |kind: &Kind| match kind {
+            Kind::HappyHour { happytimes, .. } => happytimes.descriptions(),
+            _ => Vec::new(),
+        };
+        for (before, after) in paired_times(times(&previous.kind), times(&self.kind)) {
+            field("happytimes", before, after);
+        }
+        let before = previous
+            .kind
+            .specials()
+            .iter()
+            .map(Special::to_string)
+            .collect();
+        let after = self
+            .kind
+            .specials()
+            .iter()
+            .map(Special::to_string)
+            .collect();
+        for (before, after) in paired_times(before, after) {
+            field("specials", before, after);
+        }
+        changes
+    }
+}
+
+impl Kind {
+    fn season(&self) -> Option<&Season> {
+        match self {
+            Self::HappyHour { season, .. } => season.as_ref(),
+            _ => None,
+        }
+    }
+
+    fn specials(&self) -> &[Special] {
+        match self {
+            Self::HappyHour { specials, .. } => specials,
+            _ => &[],
+        }
+    }
+}
+
+/// Pair up the times that differ, so an edited entry reads as one change.
+fn paired_times(before: Vec<String>, after: Vec<String>) -> Vec<(Option<String>, Option<String>)> {
+    let removed: Vec<_> = before.iter().filter(|t| !after.contains(t)).collect();
+    let added: Vec<_> = after.iter().filter(|t| !before.contains(t)).collect();
+    (0..removed.len().max(added.len()))
+        .map(|i| {
+            (
+                removed.get(i).map(|t| t.to_string()),
+                added.get(i).map(|t| t.to_string()),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn restaurants(source: &str) -> Restaurants<String> {
+        crate::ron_options().from_str(source).unwrap()
+    }
+
+    fn entry(name: &str, map_id: &str, kind: &str) -> String {
+        format!(
+            r#"(
+        name: "{name}",
+        url: "https://example.com/",
+        phone: None,
+        map_id: "{map_id}",
+        instagram_id: "",
+        verified: "2025-01-01",
+        kind: {kind},
+    )"#
+        )
+    }
+
+    fn happy_hour(happytimes: &str) -> String {
+        format!("HappyHour(description: [], menu_url: None, happytimes: [{happytimes}])")
+    }
+
+    #[test]
+    fn test_changes_since() {
+        let previous = restaurants(&format!(
+            "[{}, {}, {}, {}, {}]",
+            entry("Swan", "a", &happy_hour("Range((Mon, Fri), (1600, 1800))")),
+            entry("Gone", "b", "Byob"),
+            entry("Closing", "c", &happy_hour("Single(Fri, (1600, 1800))")),
+            entry("Old Name", "d", &happy_hour("Single(Sat, (1600, 1800))")),
+            entry("Same", "e", "Other"),
+        ));
+        let current = restaurants(&format!(
+            "[{}, {}, {}, {}, {}]",
+            entry("Swan", "a", &happy_hour("Range((Mon, Fri), (1500, 1800))")),
+            entry("Closing", "c", "Closed"),
+            entry("New Name", "d", &happy_hour("Single(Sat, (1600, 1800))")),
+            entry("Same", "e", "Other"),
+            entry("New", "f", "Byob"),
+        ));
+        let changes = current.changes_since(&previous);
+        assert_eq!(
+            changes,
+            vec![
+                Change::Retimed {
+                    name: "Swan".into(),
+                    slug: "swan".into(),
+                    before: vec!["Mon-Fri 4pm-6pm".into()],
+                    after: vec!["Mon-Fri 3pm-6pm".into()],
+                },
+                Change::Closed {
+                    name: "Closing".into(),
+                    slug: "closing".into(),
+                },
+                Change::Added {
+                    name: "New".into(),
+                    slug: "new".into(),
+                },
+                Change::Removed {
+                    name: "Gone".into(),
+                    slug: "gone".into(),
+                },
+            ]
+        );
+        assert_eq!(changes[0].to_string(), "New happy hours at Swan");
+        assert_eq!(
+            changes[0].summary().as_deref(),
+            Some("Mon-Fri 4pm-6pm → Mon-Fri 3pm-6pm")
+        );
+        assert!(current.changes_since(&current).is_empty());
+    }
+
+    #[test]
+    fn test_diff() {
+        let previous = restaurants(&format!(
+            "[{}, {}, {}]",
+            entry(
+                "Swan",
+                "a",
+                &happy_hour("Range((Mon, Fri), (1600, 1800)), Single(Sat, (1200, 1400))")
+            ),
+            entry("Gone", "b", "Byob"),
+            entry("Old Name", "d", "Byob"),
+        ));
+        let current = restaurants(&format!(
+            "[{}, {}, {}]",
+            entry(
+                "Swan",
+                "a",
+                &happy_hour(
+                    "Range((Mon, Fri), (1500, 1800)), Single(Sat, (1200, 1400)), Single(Sun, (1200, 1400))"
+                )
+            ),
+            entry("New Name", "d", "Closed"),
+            entry("New", "f", "Byob"),
+        ));
+        let diff = current.diff(&previous);
+        assert_eq!(
+            diff.to_string(),
+            "~ Swan
+    happytimes: Mon-Fri 4pm-6pm → Mon-Fri 3pm-6pm
+    happytimes: none → Sun 12pm-2pm
+~ New Name
+    name: Old Name → New Name
+    kind: Byob → Closed
++ New
+- Gone
+"
+        );
+        assert_eq!(
+            serde_json::to_value(&diff).unwrap()[1],
+            serde_json::json!({
+                "status": "changed",
+                "name": "New Name",
+                "changes": [
+                    {"field": "name", "before": "Old Name", "after": "New Name"},
+                    {"field": "kind", "before": "Byob", "after": "Closed"},
+                ],
+            })
+        );
+        assert!(current.diff(&current).is_empty());
+
+        let previous = restaurants(&format!("[{}]", entry("Cafe", "g", "Byob")));
+        let current = restaurants(&format!(
+            "[{}]",
+            entry(
+                "Cafe",
+                "g",
+                "Byob, open_hours: Some([Range((Tue, Sun), (1100, 2100))])"
+            )
+        ));
+        assert_eq!(
+            current.diff(&previous).to_string(),
+            "~ Cafe\n    open_hours: none → Tue-Sun 11am-9pm\n"
+        );
+    }
+}
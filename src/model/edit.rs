@@ -0,0 +1,299 @@
+use anyhow::{Result, anyhow};
+use chrono::NaiveDate;
+use phonenumber::country;
+use std::io::{BufRead, Write};
+
+use super::{DayHours, HappyTimes, Kind, Phone, Restaurant, Restaurants, check::check_url};
+use crate::prompt::Prompter;
+
+impl Restaurants {
+    /// Prompt for a new restaurant verified `today`, and insert it in name order.
+    pub fn add_interactive<R: BufRead, W: Write>(
+        &mut self,
+        prompter: &mut Prompter<R, W>,
+        today: NaiveDate,
+        phone_region: country::Id,
+    ) -> Result<String> {
+        let restaurant = prompt_restaurant(prompter, None, self, today, phone_region)?;
+        let name = restaurant.name.clone();
+        let index = self
+            .0
+            .iter()
+            .position(|r| sort_key(&r.name) > sort_key(&name))
+            .unwrap_or(self.0.len());
+        self.0.insert(index, restaurant);
+        Ok(name)
+    }
+
+    /// Prompt for new values for the restaurant called `name`, an empty answer keeps the current value.
+    pub fn edit_interactive<R: BufRead, W: Write>(
+        &mut self,
+        name: &str,
+        prompter: &mut Prompter<R, W>,
+        today: NaiveDate,
+        phone_region: country::Id,
+    ) -> Result<()> {
+        let index = self
+            .0
+            .iter()
+            .position(|r| r.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| anyhow!("No restaurant named {name:?}"))?;
+        let current = self.0[index].clone();
+        self.0[index] = prompt_restaurant(prompter, Some(&current), self, today, phone_region)?;
+        Ok(())
+    }
+}
+
+/// Restaurants are listed alphabetically, ignoring a leading "The".
+fn sort_key(name: &str) -> String {
+    let lower = name.to_lowercase();
+    lower.strip_prefix("the ").unwrap_or(&lower).to_owned()
+}
+
+fn prompt_restaurant<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    current: Option<&Restaurant>,
+    restaurants: &Restaurants,
+    today: NaiveDate,
+    phone_region: country::Id,
+) -> Result<Restaurant> {
+    let name = prompter.ask_parsed("Name", current.map(|r| r.name.as_str()), |name| {
+        let renamed = current.is_none_or(|r| r.name != name);
+        if renamed && restaurants.0.iter().any(|r| r.name == name) {
+            Err(format!("{name:?} is already listed"))
+        } else {
+            Ok(name.to_owned())
+        }
+    })?;
+    let url = prompter.ask_parsed("Website", current.map(|r| r.url.as_str()), |url| {
+        check_url(url).map(|_| url.to_owned())
+    })?;
+    let phone = prompter.ask_parsed(
+        "Phone (or none)",
+        Some(
+            current
+                .and_then(|r| r.phone.as_ref())
+                .map_or("none", Phone::as_str),
+        ),
+        |phone| {
+            if phone.eq_ignore_ascii_case("none") {
+                return Ok(None);
+            }
+            Phone::parse(phone, phone_region).map(Some)
+        },
+    )?;
+    let map_id = prompter.ask(
+        "Google Maps id (https://maps.app.goo.gl/<id>)",
+        current.map(|r| r.map_id.as_str()),
+    )?;
+    let instagram_id = prompter.ask(
+        "Instagram id",
+        Some(current.map(|r| r.instagram_id.as_str()).unwrap_or("")),
+    )?;
+    let kind = prompter.ask_parsed(
+        "Kind (happyhour, byob, other, closed)",
+        Some(current.map(|r| r.kind.name()).unwrap_or("HappyHour")),
+        |kind| match kind.to_ascii_lowercase().as_str() {
+            "happyhour" | "happy hour" | "hh" => Ok("HappyHour"),
+            "byob" => Ok("Byob"),
+            "other" => Ok("Other"),
+            "closed" => Ok("Closed"),
+            _ => Err(format!("unknown kind {kind:?}")),
+        },
+    )?;
+    let kind = match kind {
+        "Byob" => Kind::Byob,
+        "Other" => Kind::Other,
+        "Closed" => Kind::Closed,
+        _ => prompt_happy_hour(prompter, current.map(|r| &r.kind))?,
+    };
+    Ok(Restaurant {
+        name,
+        url,
+        phone,
+        map_id,
+        instagram_id,
+        verified: today,
+        open_hours: current.and_then(|r| r.open_hours.clone()),
+        kind,
+    })
+}
+
+fn prompt_happy_hour<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    current: Option<&Kind>,
+) -> Result<Kind> {
+    // Specials and seasons are kept as they are, edit them in the data file
+    let (current_description, current_menu_url, current_happytimes, specials, season) =
+        match current {
+            Some(Kind::HappyHour {
+                description,
+                menu_url,
+                happytimes,
+                specials,
+                season,
+            }) => (
+                description.clone(),
+                menu_url.clone(),
+                happytimes.0.iter().map(DayHours::to_string).collect(),
+                specials.clone(),
+                season.clone(),
+            ),
+            _ => (Vec::new(), None, Vec::new(), Vec::new(), None),
+        };
+    let description = prompter.ask_list("Description", &current_description, |line| {
+        Ok(line.to_owned())
+    })?;
+    let menu_url = prompter.ask_parsed(
+        "Happy hour menu url (or none)",
+        Some(current_menu_url.as_deref().unwrap_or("none")),
+        |url| {
+            if url.eq_ignore_ascii_case("none") {
+                return Ok(None);
+            }
+            check_url(url).map(|_| Some(url.to_owned()))
+        },
+    )?;
+    let happytimes = loop {
+        let happytimes = prompter.ask_list(
+            "Happy hours, e.g. Mon-Fri 4-6pm or Fri 11pm-1am",
+            &current_happytimes,
+            str::parse::<DayHours>,
+        )?;
+        if !happytimes.is_empty() {
+            break happytimes;
+        }
+    };
+    Ok(Kind::HappyHour {
+        description,
+        menu_url,
+        happytimes: HappyTimes(happytimes),
+        specials,
+        season,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::parse_restaurants;
+    use std::io::Cursor;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 9, 1).unwrap()
+    }
+
+    fn restaurants() -> Restaurants {
+        parse_restaurants(include_str!("../../tests/site/_data/restaurants.ron"))
+    }
+
+    fn prompter(input: &str) -> Prompter<Cursor<String>, Vec<u8>> {
+        Prompter::new(Cursor::new(input.to_owned()), Vec::new())
+    }
+
+    #[test]
+    fn test_add_interactive() {
+        let mut restaurants = restaurants();
+        let mut prompter = prompter(
+            "The Black Swan
+Cuba's Restaurant
+cubas.com
+https://www.cubasrestaurant.com/
+732-555-0199
+GxTF4n9Fp3xYV8eR6
+
+hh
+Half price sangria
+
+none
+Mon-Fri 4-6
+Mon-Fri 4-6pm
+Fri 11pm-1am
+
+",
+        );
+        let name = restaurants
+            .add_interactive(&mut prompter, today(), country::US)
+            .unwrap();
+        assert_eq!(name, "Cuba's Restaurant");
+        let index = restaurants.0.iter().position(|r| r.name == name).unwrap();
+        let added = &restaurants.0[index];
+        assert!(sort_key(&restaurants.0[index - 1].name) < sort_key(&name));
+        assert_eq!(added.url, "https://www.cubasrestaurant.com/");
+        assert_eq!(
+            added.phone.as_ref().map(Phone::as_str),
+            Some("732-555-0199")
+        );
+        assert_eq!(added.instagram_id, "");
+        assert_eq!(added.verified, today());
+        let Kind::HappyHour {
+            description,
+            menu_url,
+            happytimes,
+            ..
+        } = &added.kind
+        else {
+            panic!("not a happy hour");
+        };
+        assert_eq!(description, &vec!["Half price sangria".to_owned()]);
+        assert_eq!(menu_url, &None);
+        assert_eq!(
+            happytimes
+                .0
+                .iter()
+                .map(|dh| dh.to_string())
+                .collect::<Vec<_>>(),
+            vec!["Mon-Fri 4pm-6pm", "Fri 11pm-1am"]
+        );
+
+        let output = String::from_utf8(prompter.into_inner().1).unwrap();
+        assert!(output.contains(r#""The Black Swan" is already listed"#));
+        assert!(output.contains("relative URL without a base"));
+        assert!(output.contains(r#"add am or pm to "4-6""#));
+    }
+
+    #[test]
+    fn test_edit_interactive() {
+        let mut restaurants = restaurants();
+        let before = restaurants.0.clone();
+        // Keep everything except the hours
+        let mut prompter = prompter("\n\n\n\n\n\n\n\nMon-Fri 3-6pm\n\n");
+        restaurants
+            .edit_interactive("the black swan", &mut prompter, today(), country::US)
+            .unwrap();
+        let diff = restaurants.diff(&Restaurants(before));
+        assert_eq!(
+            diff.to_string(),
+            "~ The Black Swan
+    verified: 2025-07-28 → 2025-09-01
+    happytimes: Mon 4pm-6pm → Mon-Fri 3pm-6pm
+    happytimes: Tue 4pm-10pm → none
+    happytimes: Wed-Fri 4pm-6pm → none
+"
+        );
+        assert!(
+            restaurants
+                .edit_interactive("Nowhere", &mut prompter, today(), country::US)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_edit_clear_description() {
+        let mut restaurants = restaurants();
+        let before = restaurants.0.clone();
+        let mut prompter = prompter("\n\n\n\n\n\n-\n\n\n");
+        restaurants
+            .edit_interactive("The Black Swan", &mut prompter, today(), country::US)
+            .unwrap();
+        let diff = restaurants.diff(&Restaurants(before));
+        assert_eq!(
+            diff.to_string(),
+            "~ The Black Swan
+    verified: 2025-07-28 → 2025-09-01
+    description: 50% off all alcohol, HH food menu; Wed 2nd burger $5 → \n"
+        );
+        let (_, output) = prompter.into_inner();
+        assert!(String::from_utf8(output).unwrap().contains("- clears them"));
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{Result, anyhow};
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use super::{Day, Kind, Restaurants, slot_token};
+
+/// Name of the filter index written to the output.
+pub const FILTER_INDEX_FILE: &str = "filter-index.json";
+
+/// When each happy hour listing is on, for filtering by day and time without
+/// a selector or token per combination.
+///
+/// Bit `n` of a day's bitmap is set if the listing is on during `slots[n]`.
+/// Bitmaps are hex strings since there can be more slots than fit in a JavaScript number.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FilterIndex {
+    /// Value of the hour filter option for each slot
+    slots: Vec<String>,
+    /// Bitmaps for each day from Sunday, keyed by the `data-filter` attribute of the
+    /// element they apply to, the slug for a listing, `slug/n` for its nth happy hour
+    /// or `slug/special/n` for its nth special
+    bitmaps: BTreeMap<String, [String; 7]>,
+}
+
+impl Restaurants {
+    /// Index of the happy hours in `slot_minutes` slots on `today`, see `FilterIndex`.
+    /// Listings out of season are in no slot. Fails if two listings have the same slug,
+    /// since their bitmaps would share a key.
+    pub fn filter_index(&self, slot_minutes: u16, today: NaiveDate) -> Result<FilterIndex> {
+        let slots: Vec<u16> = self.slot_range(slot_minutes).collect();
+        let mut bitmaps = BTreeMap::new();
+        let mut seen = HashSet::new();
+        for restaurant in &self.0 {
+            let Kind::HappyHour {
+                happytimes,
+                specials,
+                ..
+            } = &restaurant.kind
+            else {
+                continue;
+            };
+            let slug = restaurant.slug();
+            if !seen.insert(slug.clone()) {
+                return Err(anyhow!(
+                    "Duplicate restaurant slug {slug:?} in the filter index for {}",
+                    restaurant.name
+                ));
+            }
+            let in_season = restaurant.kind.in_season(today);
+            let mut insert = |key: String, tuples: Vec<(Day, u16)>| {
+                let tuples = if in_season { tuples } else { Vec::new() };
+                bitmaps.insert(key, day_bitmaps(tuples, &slots));
+            };
+            for (index, dayhours) in happytimes.0.iter().enumerate() {
+                let tuples = dayhours.as_tuples(slot_minutes).collect();
+                insert(format!("{slug}/{index}"), tuples);
+            }
+            for (index, special) in specials.iter().enumerate() {
+                let tuples = special.as_tuples(happytimes, slot_minutes);
+                insert(format!("{slug}/special/{index}"), tuples);
+            }
+            let tuples = happytimes
+                .0
+                .iter()
+                .flat_map(|dh| dh.as_tuples(slot_minutes))
+                .collect();
+            insert(slug, tuples);
+        }
+        Ok(FilterIndex {
+            slots: slots
+                .iter()
+                .map(|&minutes| slot_token(minutes, slot_minutes))
+                .collect(),
+            bitmaps,
+        })
+    }
+}
+
+/// Bitmap of the `slots` in `tuples` for each day.
+fn day_bitmaps(tuples: Vec<(Day, u16)>, slots: &[u16]) -> [String; 7] {
+    let mut bitmaps = [(); 7].map(|_| vec![false; slots.len()]);
+    for (day, minutes) in tuples {
+        if let Some(bit) = slots.iter().position(|&slot| slot == minutes) {
+            bitmaps[day as usize][bit] = true;
+        }
+    }
+    bitmaps.map(|bits| hex(&bits))
+}
+
+/// Hex digits of `bits`, most significant first, with bit 0 the lowest bit of the last digit.
+fn hex(bits: &[bool]) -> String {
+    let digits: String = bits
+        .chunks(4)
+        .rev()
+        .map(|nibble| {
+            let value = nibble
+                .iter()
+                .enumerate()
+                .fold(0, |value, (i, &bit)| value | (u32::from(bit) << i));
+            char::from_digit(value, 16).expect("hex digit")
+        })
+        .collect::<String>();
+    match digits.trim_start_matches('0') {
+        "" => "0".to_string(),
+        digits => digits.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::parse_restaurants;
+    use std::{
+        io::ErrorKind,
+        path::Path,
+        process::{Command, Stdio},
+    };
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 9, 1).unwrap()
+    }
+
+    fn restaurants() -> Restaurants {
+        parse_restaurants(include_str!("../../tests/site/_data/restaurants.ron"))
+    }
+
+    /// Tokens of a `data-daytimes="..."` attribute.
+    fn daytimes(data_attributes: &str) -> Vec<String> {
+        data_attributes
+            .trim_start_matches(r#"data-daytimes=""#)
+            .trim_end_matches('"')
+            .split(' ')
+            .map(String::from)
+            .collect()
+    }
+
+    /// Shown filter keys for each `day-hour` option when `filter.js` filters by `data-daytimes`,
+    /// by the filter index, and with the index failing to load. `None` if node isn't installed.
+    fn run_filter_js(input: &serde_json::Value) -> serde_json::Value {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let child = Command::new("node")
+            .arg(dir.join("tests/filter_harness.js"))
+            .arg(dir.join("site/filter.js"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn();
+        let mut child = match child {
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                panic!("node is required to test filter.js, install it to run the tests")
+            }
+            child => child.unwrap(),
+        };
+        serde_json::to_writer(child.stdin.take().unwrap(), input).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success(), "filter_harness.js failed");
+        serde_json::from_slice(&output.stdout).unwrap()
+    }
+
+    #[test]
+    fn test_filter_js() {
+        let restaurants = restaurants();
+        for slot_minutes in [60, 30, 15] {
+            let index = restaurants.filter_index(slot_minutes, today()).unwrap();
+            // Each element's key and data-daytimes tokens
+            let mut elements = Vec::new();
+            for restaurant in &restaurants.0 {
+                if let Kind::HappyHour {
+                    happytimes,
+                    specials,
+                    ..
+                } = &restaurant.kind
+                {
+                    let slug = restaurant.slug();
+                    for (i, dayhours) in happytimes.0.iter().enumerate() {
+                        let tokens = daytimes(&dayhours.as_data_attributes(slot_minutes));
+                        elements.push((format!("{slug}/{i}"), tokens));
+                    }
+                    for (i, special) in specials.iter().enumerate() {
+                        let tokens =
+                            daytimes(&special.as_data_attributes(happytimes, slot_minutes));
+                        elements.push((format!("{slug}/special/{i}"), tokens));
+                    }
+                    let tokens = daytimes(&happytimes.as_data_attributes(slot_minutes));
+                    elements.push((slug, tokens));
+                }
+            }
+            assert!(!elements.is_empty());
+
+            let days: Vec<_> = ["all".to_string()]
+                .into_iter()
+                .chain((0..7).map(|d| d.to_string()))
+                .collect();
+            let hours: Vec<_> = ["all".to_string()]
+                .into_iter()
+                .chain(index.slots.iter().cloned())
+                .collect();
+            let input = serde_json::json!({
+                "index": index,
+                "elements": elements
+                    .iter()
+                    .map(|(key, daytimes)| serde_json::json!({"key": key, "daytimes": daytimes}))
+                    .collect::<Vec<_>>(),
+                "days": days,
+                "hours": hours,
+            });
+            let results = run_filter_js(&input);
+            for day in &days {
+                for hour in &hours {
+                    let dayhour = format!("{day}-{hour}");
+                    let expected: Vec<_> = elements
+                        .iter()
+                        .filter(|(_, tokens)| dayhour == "all-all" || tokens.contains(&dayhour))
+                        .map(|(key, _)| key.as_str())
+                        .collect();
+                    let all: Vec<_> = elements.iter().map(|(key, _)| key.as_str()).collect();
+                    for (mode, expected) in [
+                        ("daytimes", &expected),
+                        ("index", &expected),
+                        ("failed", &all),
+                    ] {
+                        assert_eq!(
+                            results[mode][&dayhour],
+                            serde_json::json!(expected),
+                            "{mode} for {dayhour} in {slot_minutes} minute slots"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_filter_index() {
+        let restaurants = parse_restaurants(
+            r#"[
+    (
+        name: "The Black Swan",
+        url: "https://www.theblackswanap.com/",
+        phone: None,
+        map_id: "JiKYhYvKsK2ysBZs9",
+        instagram_id: "theblackswanap",
+        verified: "2025-07-28",
+        kind: HappyHour(
+            description: ["50% off all alcohol"],
+            menu_url: None,
+            happytimes: [
+                Range((Wed, Fri), (1600, 1800)),
+                Single(Sat, (1630, 2000)),
+            ],
+            specials: [
+                (days: Range((Sun, Mon)), hours: Some((2000, 2130)), item: "Wings"),
+            ],
+        ),
+    ),
+]"#,
+        );
+        // The special is after every happy hour, so has slots of its own
+        assert_eq!(
+            serde_json::to_value(restaurants.filter_index(60, today()).unwrap()).unwrap(),
+            serde_json::json!({
+                "slots": ["16", "17", "18", "19", "20", "21"],
+                "bitmaps": {
+                    "the-black-swan": ["0", "0", "0", "3", "3", "3", "e"],
+                    "the-black-swan/0": ["0", "0", "0", "3", "3", "3", "0"],
+                    "the-black-swan/1": ["0", "0", "0", "0", "0", "0", "e"],
+                    "the-black-swan/special/0": ["30", "30", "0", "0", "0", "0", "0"],
+                },
+            })
+        );
+        let index = restaurants.filter_index(30, today()).unwrap();
+        assert_eq!(index.slots.len(), 11);
+        assert_eq!(index.bitmaps["the-black-swan/special/0"][0], "700");
+        assert_eq!(index.bitmaps["the-black-swan"][6], "fe");
+        assert_eq!(index.bitmaps["the-black-swan"][3], "f");
+
+        // Names differing only in punctuation would share bitmaps
+        let mut restaurants = restaurants;
+        let mut other = restaurants.0[0].clone();
+        other.name = "The Black-Swan".into();
+        restaurants.0.push(other);
+        let err = restaurants.filter_index(60, today()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"Duplicate restaurant slug "the-black-swan" in the filter index for The Black-Swan"#
+        );
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(hex(&[]), "0");
+        assert_eq!(hex(&[false, true, true, true, true]), "1e");
+    }
+}
@@ -0,0 +1,223 @@
+use chrono::NaiveDate;
+use minijinja::{Environment, Error, ErrorKind, Value};
+
+use super::{Day, DayHours, Hour, Hours, Kind, Phone, RenderOptions, object::RestaurantValue};
+
+/// Add the filters and functions for formatting restaurant data in templates,
+/// using the build date and phone region from `options`.
+pub fn add_template_functions(env: &mut Environment, options: &RenderOptions) {
+    let today = options.today;
+    let phone_region = options.phone_region;
+    env.add_filter("format_hours", format_hours);
+    env.add_filter("format_phone", move |phone: &str, region: Option<&str>| {
+        format_phone(phone, region, phone_region)
+    });
+    env.add_filter("days_ago", move |date: &str| days_ago(date, today));
+    env.add_filter("maps_url", |map_id: &str| maps_url(map_id));
+    env.add_filter("instagram_url", |id: &str| instagram_url(id));
+    env.add_filter("slugify", |s: &str| super::slugify(s));
+    env.add_function("is_open_at", is_open_at);
+}
+
+/// Google Maps link for a `map_id`.
+pub(super) fn maps_url(map_id: &str) -> String {
+    format!("https://maps.app.goo.gl/{map_id}")
+}
+
+/// Instagram profile link for an `instagram_id`.
+pub(super) fn instagram_url(id: &str) -> String {
+    format!("https://www.instagram.com/{id}")
+}
+
+fn invalid(message: impl Into<std::borrow::Cow<'static, str>>) -> Error {
+    Error::new(ErrorKind::InvalidOperation, message)
+}
+
+/// A time as displayed, from e.g. `1630`, `[1600, 1800]` or `"mon-fri 4-6pm"`.
+fn format_hours(value: &Value) -> Result<String, Error> {
+    let hour =
+        |value: Value| -> Result<Hour, Error> { u16::try_from(value)?.try_into().map_err(invalid) };
+    if let Some(s) = value.as_str() {
+        return Ok(s.parse::<DayHours>().map_err(invalid)?.to_string());
+    }
+    if value.as_i64().is_some() {
+        return Ok(hour(value.clone())?.to_string());
+    }
+    let hours: Vec<_> = value.try_iter()?.collect();
+    match <[Value; 2]>::try_from(hours) {
+        Ok([start, end]) => Ok(Hours(hour(start)?, hour(end)?).to_string()),
+        Err(_) => Err(invalid(format!(
+            "cannot format {value} as hours, expected e.g. 1630 or [1600, 1800]"
+        ))),
+    }
+}
+
+/// A phone number as displayed for `region`, a country code such as "GB" for numbers without one.
+fn format_phone(
+    phone: &str,
+    region: Option<&str>,
+    default_region: phonenumber::country::Id,
+) -> Result<String, Error> {
+    let region = match region {
+        Some(region) => region
+            .parse()
+            .map_err(|_| invalid(format!("unknown phone region {region:?}")))?,
+        None => default_region,
+    };
+    let phone = Phone::parse(phone, region).map_err(invalid)?;
+    Ok(phone.display(region))
+}
+
+/// Days from a date such as `restaurant.verified` to the build date.
+fn days_ago(date: &str, today: NaiveDate) -> Result<i64, Error> {
+    let date: NaiveDate = date
+        .parse()
+        .map_err(|err| invalid(format!("invalid date {date:?}: {err}")))?;
+    Ok((today - date).num_days())
+}
+
+/// Whether the restaurant is open at `hour` (e.g. 1630) on `day`,
+/// a day index as in `restaurants.day_options` or a name such as "Mon".
+/// Uses `open_hours` if given, otherwise the in-season happy hours, so it is false
+/// for restaurants with neither.
+fn is_open_at(value: &Value, day: &Value, hour: u16) -> Result<bool, Error> {
+    let RestaurantValue {
+        restaurant,
+        options,
+    } = value
+        .downcast_object_ref()
+        .ok_or_else(|| invalid(format!("is_open_at expects a restaurant, not {value}")))?;
+    let day = match day.as_str() {
+        Some(day) => day.parse().map_err(invalid)?,
+        None => match usize::try_from(day.clone())? {
+            index @ 0..=6 => Day::from_index(index),
+            index => return Err(invalid(format!("invalid day {index}, expected 0 to 6"))),
+        },
+    };
+    let hour = Hour::try_from(hour).map_err(invalid)?;
+    // 2400 and later is early the next morning
+    let day = if hour.0 >= 2400 {
+        Day::from_index(day as usize + 1)
+    } else {
+        day
+    };
+    let minutes = hour.wraparound().minutes_of_day();
+    Ok(match (&restaurant.open_hours, &restaurant.kind) {
+        (Some(open_hours), _) => open_hours.is_active_at(day, minutes),
+        (None, Kind::HappyHour { happytimes, .. }) => {
+            restaurant.kind.in_season(options.today) && happytimes.is_active_at(day, minutes)
+        }
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::parse_restaurants;
+    use crate::model::restaurant_values;
+    use minijinja::context;
+
+    #[test]
+    fn test_template_functions() {
+        let mut restaurants =
+            parse_restaurants(include_str!("../../tests/site/_data/restaurants.ron"));
+        let ada = restaurants
+            .0
+            .iter_mut()
+            .find(|r| r.name == "Ada's Gojjo")
+            .unwrap();
+        ada.open_hours = Some(
+            crate::ron_options()
+                .from_str("[Range((Tue, Sun), (1100, 2100))]")
+                .unwrap(),
+        );
+        let catbird = restaurants
+            .0
+            .iter_mut()
+            .find(|r| r.name == "Catbird Asbury Park")
+            .unwrap();
+        catbird.open_hours = Some(
+            crate::ron_options()
+                .from_str("[Single(Fri, (2300, 2500))]")
+                .unwrap(),
+        );
+        let options = RenderOptions {
+            today: NaiveDate::from_ymd_opt(2025, 9, 1).unwrap(),
+            ..Default::default()
+        };
+        let values = restaurant_values(&restaurants, options.clone());
+        let value = |slug: &str| {
+            values
+                .iter()
+                .find(|(s, _)| s == slug)
+                .map(|(_, value)| value.clone())
+                .unwrap()
+        };
+        let black_swan = value("the-black-swan");
+        let mut env = Environment::new();
+        add_template_functions(&mut env, &options);
+        let render = |source| env.render_str(source, context! { restaurant => black_swan.clone() });
+
+        assert_eq!(render("{{ 1630 | format_hours }}").unwrap(), "4:30pm");
+        assert_eq!(
+            render("{{ [2300, 2500] | format_hours }}").unwrap(),
+            "11pm-1am"
+        );
+        assert_eq!(
+            render("{{ 'mon-fri 4-6pm' | format_hours }}").unwrap(),
+            "Mon-Fri 4pm-6pm"
+        );
+        assert!(render("{{ 1690 | format_hours }}").is_err());
+        assert!(render("{{ [1600] | format_hours }}").is_err());
+
+        assert_eq!(
+            render("{{ '732-555-4433' | format_phone }}").unwrap(),
+            "(732) 555-4433"
+        );
+        assert_eq!(
+            render("{{ '020 7946 0958' | format_phone('GB') }}").unwrap(),
+            "020 7946 0958"
+        );
+        assert!(render("{{ '555' | format_phone('XX') }}").is_err());
+
+        assert_eq!(
+            render("{{ restaurant.verified | days_ago }}").unwrap(),
+            "35"
+        );
+        assert_eq!(
+            render("{{ restaurant.map_id | maps_url }}").unwrap(),
+            "https://maps.app.goo.gl/JiKYhYvKsK2ysBZs9"
+        );
+        assert_eq!(
+            render("{{ restaurant.instagram_id | instagram_url }}").unwrap(),
+            "https://www.instagram.com/theblackswanap"
+        );
+        assert_eq!(
+            render("{{ \"Ada's Gojjo\" | slugify }}").unwrap(),
+            "adas-gojjo"
+        );
+
+        let is_open_at =
+            |restaurant: &Value, day: Value, hour| is_open_at(restaurant, &day, hour).unwrap();
+        // Happy hour Mon 4pm-6pm
+        assert!(is_open_at(&black_swan, Value::from(1), 1700));
+        assert!(!is_open_at(&black_swan, Value::from("Mon"), 1800));
+        // Open Tue-Sun 11am-9pm, no happy hour
+        let ada = value("adas-gojjo");
+        assert!(is_open_at(&ada, Value::from("Tue"), 1100));
+        assert!(!is_open_at(&ada, Value::from("Mon"), 1200));
+        // Open Fri 11pm-1am, after midnight is the same as early Saturday
+        let catbird = value("catbird-asbury-park");
+        assert!(is_open_at(&catbird, Value::from("Fri"), 2430));
+        assert!(is_open_at(&catbird, Value::from("Sat"), 30));
+        assert!(!is_open_at(&catbird, Value::from("Thu"), 2430));
+        assert!(!is_open_at(&catbird, Value::from("Fri"), 30));
+        assert_eq!(
+            render("{% if is_open_at(restaurant, 1, 1700) %}open{% endif %}").unwrap(),
+            "open"
+        );
+        assert!(render("{{ is_open_at(restaurant, 7, 1700) }}").is_err());
+        assert!(render("{{ is_open_at('The Black Swan', 1, 1700) }}").is_err());
+    }
+}
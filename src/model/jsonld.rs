@@ -0,0 +1,221 @@
+use serde_json::{Value, json};
+
+use super::{
+    Day, DayHours, HappyTimes, Hour, Kind, Phone, Restaurant,
+    functions::{instagram_url, maps_url},
+    iter_days,
+};
+
+impl Restaurant {
+    /// Schema.org structured data describing the restaurant, `None` if it has closed.
+    pub(super) fn json_ld(&self) -> Option<Value> {
+        let kind = match self.kind {
+            Kind::Closed => return None,
+            Kind::Byob => "Restaurant",
+            Kind::HappyHour { .. } | Kind::Other => "BarOrPub",
+        };
+        let mut json_ld = json!({
+            "@context": "https://schema.org",
+            "@type": kind,
+            "name": self.name,
+            "url": self.url,
+            "hasMap": maps_url(&self.map_id),
+        });
+        if !self.instagram_id.is_empty() {
+            json_ld["sameAs"] = json!([instagram_url(&self.instagram_id)]);
+        }
+        if let Some(url) = self.phone.as_ref().map(Phone::url) {
+            json_ld["telephone"] = json!(url.trim_start_matches("tel:"));
+        }
+        if let Some(open_hours) = &self.open_hours {
+            json_ld["openingHoursSpecification"] = json!(open_hours.opening_hours_specification());
+        }
+        if let Kind::HappyHour {
+            description,
+            menu_url,
+            happytimes,
+            specials,
+            ..
+        } = &self.kind
+        {
+            let description: Vec<_> = description
+                .iter()
+                .cloned()
+                .chain(specials.iter().map(ToString::to_string))
+                .collect();
+            let mut offer = json!({
+                "@type": "Offer",
+                "name": "Happy Hour",
+                "description": description.join(", "),
+                "itemOffered": {
+                    "@type": "Service",
+                    "name": "Happy Hour",
+                    "hoursAvailable": happytimes.opening_hours_specification(),
+                },
+            });
+            if let Some(menu_url) = menu_url {
+                offer["url"] = json!(menu_url);
+            }
+            json_ld["makesOffer"] = json!([offer]);
+        }
+        Some(json_ld)
+    }
+}
+
+impl HappyTimes {
+    fn opening_hours_specification(&self) -> Vec<Value> {
+        self.0
+            .iter()
+            .map(DayHours::opening_hours_specification)
+            .collect()
+    }
+}
+
+impl DayHours {
+    fn opening_hours_specification(&self) -> Value {
+        let hours = self.hours();
+        json!({
+            "@type": "OpeningHoursSpecification",
+            "dayOfWeek": iter_days(self.days()).map(|day| day.schema_org()).collect::<Vec<_>>(),
+            "opens": hours.0.schema_org(),
+            "closes": hours.1.schema_org(),
+        })
+    }
+}
+
+impl Day {
+    fn schema_org(&self) -> &'static str {
+        match self {
+            Self::Sun => "https://schema.org/Sunday",
+            Self::Mon => "https://schema.org/Monday",
+            Self::Tue => "https://schema.org/Tuesday",
+            Self::Wed => "https://schema.org/Wednesday",
+            Self::Thu => "https://schema.org/Thursday",
+            Self::Fri => "https://schema.org/Friday",
+            Self::Sat => "https://schema.org/Saturday",
+        }
+    }
+}
+
+impl Hour {
+    /// Time of day as `HH:MM`, times past midnight wrap to the next day.
+    fn schema_org(&self) -> String {
+        let t = self.wraparound();
+        format!("{:02}:{:02}", t.hours(), t.minutes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::parse_restaurants;
+
+    #[test]
+    fn test_json_ld() {
+        let restaurants = parse_restaurants(
+            r#"[
+    (
+        name: "The Black Swan",
+        url: "https://www.theblackswanap.com/",
+        phone: Some("732-555-4433"),
+        map_id: "JiKYhYvKsK2ysBZs9",
+        instagram_id: "theblackswanap",
+        verified: "2025-07-28",
+        kind: HappyHour(
+            description: [
+                "50% off all alcohol, HH food menu",
+                "Wed 2nd burger $5",
+            ],
+            menu_url: Some("https://www.theblackswanap.com/happy-hour"),
+            happytimes: [
+                Range((Wed, Fri), (1600, 1800)),
+                Single(Sat, (2300, 2500)),
+            ],
+        ),
+    ),
+    (
+        name: "Asbury Festhalle & Biergarten",
+        url: "https://www.asburybiergarten.com/",
+        phone: None,
+        map_id: "pCUcJKrohgecvbse8",
+        instagram_id: "apbiergarten",
+        verified: "2024-12-30",
+        kind: Closed,
+    ),
+]"#,
+        );
+        assert_eq!(
+            restaurants.0[0].json_ld(),
+            Some(json!({
+                "@context": "https://schema.org",
+                "@type": "BarOrPub",
+                "name": "The Black Swan",
+                "url": "https://www.theblackswanap.com/",
+                "hasMap": "https://maps.app.goo.gl/JiKYhYvKsK2ysBZs9",
+                "sameAs": ["https://www.instagram.com/theblackswanap"],
+                "telephone": "+1-732-555-4433",
+                "makesOffer": [{
+                    "@type": "Offer",
+                    "name": "Happy Hour",
+                    "description": "50% off all alcohol, HH food menu, Wed 2nd burger $5",
+                    "url": "https://www.theblackswanap.com/happy-hour",
+                    "itemOffered": {
+                        "@type": "Service",
+                        "name": "Happy Hour",
+                        "hoursAvailable": [
+                            {
+                                "@type": "OpeningHoursSpecification",
+                                "dayOfWeek": [
+                                    "https://schema.org/Wednesday",
+                                    "https://schema.org/Thursday",
+                                    "https://schema.org/Friday",
+                                ],
+                                "opens": "16:00",
+                                "closes": "18:00",
+                            },
+                            {
+                                "@type": "OpeningHoursSpecification",
+                                "dayOfWeek": ["https://schema.org/Saturday"],
+                                "opens": "23:00",
+                                "closes": "01:00",
+                            },
+                        ],
+                    },
+                }],
+            }))
+        );
+        assert_eq!(restaurants.0[1].json_ld(), None);
+    }
+
+    #[test]
+    fn test_json_ld_open_hours() {
+        let restaurants = parse_restaurants(
+            r#"[(
+    name: "Dolce Fantasia",
+    url: "https://example.com/",
+    phone: None,
+    map_id: "a",
+    instagram_id: "",
+    verified: "2025-07-28",
+    open_hours: Some([Range((Tue, Sun), (1100, 2100))]),
+    kind: Byob,
+)]"#,
+        );
+        assert_eq!(
+            restaurants.0[0].json_ld().unwrap()["openingHoursSpecification"],
+            json!([{
+                "@type": "OpeningHoursSpecification",
+                "dayOfWeek": [
+                    "https://schema.org/Tuesday",
+                    "https://schema.org/Wednesday",
+                    "https://schema.org/Thursday",
+                    "https://schema.org/Friday",
+                    "https://schema.org/Saturday",
+                    "https://schema.org/Sunday",
+                ],
+                "opens": "11:00",
+                "closes": "21:00",
+            }])
+        );
+    }
+}
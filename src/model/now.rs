@@ -0,0 +1,228 @@
+use chrono::{DateTime, Datelike, NaiveDateTime, TimeZone, Timelike};
+use chrono_tz::Tz;
+
+use super::{Day, DayHours, HappyTimes, Kind, Restaurants, iter_days};
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+impl Restaurants {
+    /// Names of the restaurants in happy hour at `when`, evaluated in local time for `tz`.
+    pub fn happening_at<T: TimeZone>(&self, when: &DateTime<T>, tz: Tz) -> Vec<&str> {
+        let local = when.with_timezone(&tz);
+        let day = Day::from(local.weekday());
+        let minutes = (local.hour() * 60 + local.minute()) as u16;
+        self.0
+            .iter()
+            .filter(|restaurant| match &restaurant.kind {
+                Kind::HappyHour { happytimes, .. } => {
+                    restaurant.kind.in_season(local.date_naive())
+                        && happytimes.is_active_at(day, minutes)
+                }
+                _ => false,
+            })
+            .map(|restaurant| restaurant.name.as_str())
+            .collect()
+    }
+}
+
+/// Parse an RFC 3339 timestamp, or a wall clock time such as `2025-08-01 18:00` in `tz`.
+pub fn parse_datetime(s: &str, tz: Tz) -> Result<DateTime<Tz>, String> {
+    if let Ok(when) = DateTime::parse_from_rfc3339(s) {
+        return Ok(when.with_timezone(&tz));
+    }
+    let naive = [
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M:%S",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+    .ok_or_else(|| format!("invalid date/time {s:?}, expected e.g. 2025-08-01 18:00"))?;
+    local_datetime(naive, tz).ok_or_else(|| format!("{s} does not exist in {tz}"))
+}
+
+/// Interpret a wall clock time in `tz`, preferring the earlier time when it is ambiguous.
+fn local_datetime(naive: NaiveDateTime, tz: Tz) -> Option<DateTime<Tz>> {
+    tz.from_local_datetime(&naive).earliest()
+}
+
+impl HappyTimes {
+    /// Whether any entry is active on `day` at `minutes` past local midnight.
+    pub(super) fn is_active_at(&self, day: Day, minutes: u16) -> bool {
+        self.0.iter().any(|dh| dh.is_active_at(day, minutes))
+    }
+}
+
+impl DayHours {
+    fn is_active_at(&self, day: Day, minutes: u16) -> bool {
+        let hours = self.hours();
+        let start = hours.0.hours() * 60 + hours.0.minutes();
+        let end = hours.1.hours() * 60 + hours.1.minutes();
+        let previous = Day::from_index((day as usize + 6) % 7);
+        iter_days(self.days()).any(|d| {
+            // Hours from 2400 continue into the early hours of the next day
+            (d == day && (start..end).contains(&minutes))
+                || (d == previous && (start..end).contains(&(minutes + MINUTES_PER_DAY)))
+        })
+    }
+}
+
+impl From<chrono::Weekday> for Day {
+    fn from(weekday: chrono::Weekday) -> Self {
+        Day::from_index(weekday.num_days_from_sunday() as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::parse_restaurants;
+    use chrono::Utc;
+    use chrono_tz::America::New_York;
+
+    fn restaurants() -> Restaurants {
+        parse_restaurants(
+            r#"[
+    (
+        name: "Late Night",
+        url: "https://example.com/",
+        phone: None,
+        map_id: "a",
+        instagram_id: "a",
+        verified: "2025-01-01",
+        kind: HappyHour(
+            description: [],
+            menu_url: None,
+            happytimes: [
+                Single(Fri, (2300, 2500)),
+                Single(Sat, (2300, 2500)),
+                Single(Sun, (0100, 0400)),
+            ],
+        ),
+    ),
+    (
+        name: "Early",
+        url: "https://example.com/",
+        phone: None,
+        map_id: "b",
+        instagram_id: "b",
+        verified: "2025-01-01",
+        kind: HappyHour(
+            description: [],
+            menu_url: None,
+            happytimes: [
+                Range((Mon, Fri), (1600, 1830)),
+            ],
+        ),
+    ),
+    (
+        name: "Byob",
+        url: "https://example.com/",
+        phone: None,
+        map_id: "c",
+        instagram_id: "c",
+        verified: "2025-01-01",
+        kind: Byob,
+    ),
+]"#,
+        )
+    }
+
+    fn at(rfc3339: &str) -> Vec<String> {
+        let when = DateTime::parse_from_rfc3339(rfc3339).unwrap();
+        restaurants()
+            .happening_at(&when, New_York)
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_happening_at() {
+        // Fri 2025-08-01 16:00 EDT
+        assert_eq!(at("2025-08-01T20:00:00Z"), vec!["Early"]);
+        assert_eq!(at("2025-08-01T22:29:00Z"), vec!["Early"]);
+        assert!(at("2025-08-01T22:30:00Z").is_empty());
+        // Fri 2300-2500 is active into Sat 00:30
+        assert_eq!(at("2025-08-02T03:00:00Z"), vec!["Late Night"]);
+        assert_eq!(at("2025-08-02T04:30:00Z"), vec!["Late Night"]);
+        assert!(at("2025-08-02T05:00:00Z").is_empty());
+        // Sat 2300-2500 wraps into Sun, Sun 0100-0400 does not wrap into Mon
+        assert_eq!(at("2025-08-03T04:59:00Z"), vec!["Late Night"]);
+        assert!(at("2025-08-04T05:00:00Z").is_empty());
+    }
+
+    #[test]
+    fn test_happening_at_season() {
+        let restaurants = parse_restaurants(
+            r#"[
+    (
+        name: "Beach Bar",
+        url: "https://example.com/",
+        phone: None,
+        map_id: "a",
+        instagram_id: "",
+        verified: "2025-01-01",
+        kind: HappyHour(
+            description: [],
+            menu_url: None,
+            happytimes: [Single(Fri, (1600, 1800))],
+            season: Some(Summer),
+        ),
+    ),
+]"#,
+        );
+        let at = |rfc3339| {
+            let when = DateTime::parse_from_rfc3339(rfc3339).unwrap();
+            restaurants.happening_at(&when, New_York).len()
+        };
+        // Fri 2025-08-29 and Fri 2025-09-05 16:00 EDT, Labor Day is 2025-09-01
+        assert_eq!(at("2025-08-29T20:00:00Z"), 1);
+        assert_eq!(at("2025-09-05T20:00:00Z"), 0);
+    }
+
+    #[test]
+    fn test_happening_at_dst() {
+        // Sun 0100-0400 on 2025-03-09, clocks spring forward at 02:00 so it lasts two hours
+        assert_eq!(at("2025-03-09T06:00:00Z"), vec!["Late Night"]);
+        assert_eq!(at("2025-03-09T07:00:00Z"), vec!["Late Night"]);
+        assert!(at("2025-03-09T08:00:00Z").is_empty());
+        // Sun 0100-0400 on 2025-11-02, clocks fall back at 02:00 so it lasts four hours
+        assert_eq!(at("2025-11-02T05:00:00Z"), vec!["Late Night"]);
+        assert_eq!(at("2025-11-02T06:30:00Z"), vec!["Late Night"]);
+        assert_eq!(at("2025-11-02T08:59:00Z"), vec!["Late Night"]);
+        assert!(at("2025-11-02T09:00:00Z").is_empty());
+        // Weekday happy hour follows local time across the change
+        assert_eq!(at("2025-11-03T21:00:00Z"), vec!["Early"]);
+        assert!(at("2025-11-03T23:30:00Z").is_empty());
+    }
+
+    #[test]
+    fn test_local_datetime() {
+        let naive = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M").unwrap();
+        assert_eq!(
+            local_datetime(naive("2025-08-01T18:00"), New_York).map(|dt| dt.with_timezone(&Utc)),
+            Some(Utc.with_ymd_and_hms(2025, 8, 1, 22, 0, 0).unwrap())
+        );
+        // Nonexistent during spring forward
+        assert_eq!(local_datetime(naive("2025-03-09T02:30"), New_York), None);
+        // Ambiguous during fall back resolves to the first (EDT) occurrence
+        assert_eq!(
+            local_datetime(naive("2025-11-02T01:30"), New_York).map(|dt| dt.with_timezone(&Utc)),
+            Some(Utc.with_ymd_and_hms(2025, 11, 2, 5, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_datetime() {
+        let utc = |s| parse_datetime(s, New_York).map(|dt| dt.with_timezone(&Utc));
+        let expected = Ok(Utc.with_ymd_and_hms(2025, 8, 1, 22, 0, 0).unwrap());
+        assert_eq!(utc("2025-08-01 18:00"), expected);
+        assert_eq!(utc("2025-08-01T18:00:00"), expected);
+        assert_eq!(utc("2025-08-01T18:00:00-04:00"), expected);
+        assert_eq!(utc("2025-08-01T22:00:00Z"), expected);
+        assert!(utc("2025-03-09 02:30").is_err());
+        assert!(utc("Friday").is_err());
+    }
+}
@@ -5,56 +5,66 @@ use minijinja::{
     value::{Enumerator, Object},
 };
 
-pub fn restaurants_value(mut restaurants: super::Restaurants) -> Value {
+const NO_DAYTIMES: &str = r#"data-daytimes="""#;
+
+pub fn restaurants_value(
+    mut restaurants: super::Restaurants,
+    options: super::RenderOptions,
+) -> Value {
+    let options = Arc::new(options);
     let mut happy_hour = Vec::new();
     let mut byob = Vec::new();
     let mut other = Vec::new();
     let mut closed = Vec::new();
-    let mut min_hour = super::Hour(2500);
-    let mut max_hour = super::Hour(0000);
+    // Slots from the earliest start to the latest end, in minutes past midnight
+    let slot_minutes = options.slot_minutes;
+    let slot_range = restaurants.slot_range(slot_minutes);
     for restaurant in restaurants.0.drain(..) {
         match restaurant.kind {
-            super::Kind::HappyHour { ref happytimes, .. } => {
-                happytimes.0.iter().for_each(|dh| match dh {
-                    super::DayHours::Single(_, hours) | super::DayHours::Range(_, hours) => {
-                        if hours.0 < min_hour {
-                            min_hour = hours.0;
-                        }
-                        if hours.1 > max_hour {
-                            max_hour = hours.1;
-                        }
-                    }
-                });
-                happy_hour.push(restaurant);
-            }
+            super::Kind::HappyHour { .. } => happy_hour.push(restaurant),
             super::Kind::Byob => byob.push(restaurant),
             super::Kind::Other => other.push(restaurant),
             super::Kind::Closed => closed.push(restaurant),
         }
     }
 
-    let hour_range = if max_hour.minutes() > 0 {
-        min_hour.hours()..(max_hour.hours() + 1)
+    // Selectors for every combination grow quadratically, so they are only
+    // generated for hour slots and finer slots or the filter index are handled by filter.js
+    let dayhours = if slot_minutes == super::HOUR_SLOT && !options.filter_index {
+        (0..=6)
+            .map(|d| d.to_string())
+            .chain(once("all".to_string()))
+            .flat_map(|d| {
+                slot_range
+                    .clone()
+                    .map(|m| super::slot_token(m, slot_minutes))
+                    .chain(once("all".to_string()))
+                    .filter_map(move |h| {
+                        if d == "all" && h == "all" {
+                            None
+                        } else {
+                            Some(format!("{d}-{h}"))
+                        }
+                    })
+            })
+            .collect::<Vec<_>>()
     } else {
-        min_hour.hours()..max_hour.hours()
+        Vec::new()
     };
 
     context! {
-        happy_hour => Value::make_iterable(move || happy_hour.clone().into_iter().map(Value::from_object)),
-        byob => Value::make_iterable(move || byob.clone().into_iter().map(Value::from_object)),
-        other => Value::make_iterable(move || other.clone().into_iter().map(Value::from_object)),
-        closed => Value::make_iterable(move || closed.clone().into_iter().map(Value::from_object)),
+        happy_hour => restaurant_list(happy_hour, &options),
+        byob => restaurant_list(byob, &options),
+        other => restaurant_list(other, &options),
+        closed => restaurant_list(closed, &options),
+        calendar => options.calendars.then_some("calendar/happy-hours.ics"),
+        feed => options.feed.then_some(crate::feed::FEED_FILE),
+        filter_index => options.filter_index.then_some(super::FILTER_INDEX_FILE),
         hour_options => Value::from_serialize(
-            hour_range.clone()
-                .map(|h| {
-                    let display = match h {
-                        12 => "12pm".to_string(),
-                        1..12 => format!("{h}am"),
-                        25.. => format!("{}am", h - 24),
-                        13..=24 => format!("{}pm", h - 12),
-                        _ => unreachable!(),
-                    };
-                    (h, display)
+            slot_range.clone()
+                .map(|m| {
+                    let hour = super::Hour(m / 60 * 100 + m % 60);
+                    (super::slot_token(m, slot_minutes), hour.to_string())
                 })
                 .collect::<Vec<_>>(),
         ),
@@ -63,55 +73,79 @@ pub fn restaurants_value(mut restaurants: super::Restaurants) -> Value {
                 .map(|d| (d as isize, format!("{d}")))
                 .collect::<Vec<_>>(),
         ),
-        dayhours => Value::from(
-            (0..=6)
-                .map(|d| d.to_string())
-                .chain(once("all".to_string()))
-                .flat_map(|d| {
-                    hour_range.clone()
-                        .map(|h| h.to_string())
-                        .chain(once("all".to_string()))
-                        .filter_map(move |h| {
-                            if d == "all" && h == "all" {
-                                None
-                            } else {
-                                Some(format!("{d}-{h}"))
-                            }
-                        })
-                })
-                .collect::<Vec<_>>(),
-        ),
+        dayhours => Value::from(dayhours),
     }
 }
 
-impl Object for super::Restaurant {
+/// Every restaurant as a template value, paired with its slug.
+pub fn restaurant_values(
+    restaurants: &super::Restaurants,
+    options: super::RenderOptions,
+) -> Vec<(String, Value)> {
+    let options = Arc::new(options);
+    restaurants
+        .0
+        .iter()
+        .map(|restaurant| {
+            (
+                restaurant.slug(),
+                Value::from_object(RestaurantValue {
+                    restaurant: restaurant.clone(),
+                    options: options.clone(),
+                }),
+            )
+        })
+        .collect()
+}
+
+/// A restaurant as seen by templates, along with the options it is rendered with.
+#[derive(Debug)]
+pub(super) struct RestaurantValue {
+    pub(super) restaurant: super::Restaurant,
+    pub(super) options: Arc<super::RenderOptions>,
+}
+
+fn restaurant_list(
+    restaurants: Vec<super::Restaurant>,
+    options: &Arc<super::RenderOptions>,
+) -> Value {
+    let options = options.clone();
+    Value::make_iterable(move || {
+        let options = options.clone();
+        restaurants.clone().into_iter().map(move |restaurant| {
+            Value::from_object(RestaurantValue {
+                restaurant,
+                options: options.clone(),
+            })
+        })
+    })
+}
+
+impl Object for RestaurantValue {
     fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
         match key.as_str()? {
-            "name" => Some(Value::from(&self.name)),
-            "url" => Some(Value::from(&self.url)),
-            "phone" => self.phone.as_ref().map(|phone| {
-                let phone = phonenumber::parse(Some(phonenumber::country::US), phone)
-                    .expect("phone number");
-                context! {
-                    display => Value::from(
-                        phone
-                            .format()
-                            .mode(phonenumber::Mode::National)
-                            .to_string(),
-                    ),
-                    url => Value::from(
-                        phone
-                            .format()
-                            .mode(phonenumber::Mode::Rfc3966)
-                            .to_string()
-                    ),
-                }
-            }),
-            "map_id" => Some(Value::from(&self.map_id)),
-            "instagram_id" => Some(Value::from(&self.instagram_id)),
-            "verified" => Some(Value::from_serialize(self.verified)),
+            "name" => Some(Value::from(&self.restaurant.name)),
+            "slug" => Some(Value::from(self.restaurant.slug())),
+            "url" => Some(Value::from(&self.restaurant.url)),
+            "phone" => {
+                let phone = self.restaurant.phone.as_ref()?;
+                Some(context! {
+                    display => phone.display(self.options.phone_region),
+                    url => phone.url(),
+                })
+            }
+            "map_id" => Some(Value::from(&self.restaurant.map_id)),
+            "instagram_id" => Some(Value::from(&self.restaurant.instagram_id)),
+            "verified" => Some(Value::from_serialize(self.restaurant.verified)),
+            "days_since_verified" => Some(Value::from(
+                self.restaurant.days_since_verified(self.options.today),
+            )),
+            "stale" => Some(Value::from(
+                self.restaurant.days_since_verified(self.options.today)
+                    > self.options.stale_after_days,
+            )),
             "description" => {
-                if let super::Kind::HappyHour { description, .. } = &self.kind {
+                if let super::Kind::HappyHour { description, .. } = &self.restaurant.kind {
                     Some(Value::from_serialize(description))
                 } else {
                     None
@@ -121,27 +155,99 @@ impl Object for super::Restaurant {
                 if let super::Kind::HappyHour {
                     menu_url: Some(menu_url),
                     ..
-                } = &self.kind
+                } = &self.restaurant.kind
                 {
                     Some(Value::from_serialize(menu_url))
                 } else {
                     None
                 }
             }
+            "json_ld" => self.restaurant.json_ld().map(|json_ld| {
+                // Keep the JSON from closing the script element it is embedded in
+                Value::from(json_ld.to_string().replace("</", "<\\/"))
+            }),
+            "specials" => match &self.restaurant.kind {
+                super::Kind::HappyHour {
+                    happytimes,
+                    specials,
+                    ..
+                } if !specials.is_empty() => {
+                    let slug = self.restaurant.slug();
+                    let in_season = self.restaurant.kind.in_season(self.options.today);
+                    let specials: Vec<_> = specials
+                        .iter()
+                        .enumerate()
+                        .map(|(index, special)| {
+                            let data_attributes = if self.options.filter_index {
+                                format!(r#"data-filter="{slug}/special/{index}""#)
+                            } else if in_season {
+                                special.as_data_attributes(happytimes, self.options.slot_minutes)
+                            } else {
+                                NO_DAYTIMES.to_string()
+                            };
+                            context! {
+                                text => special.to_string(),
+                                when => special.when(),
+                                item => special.item,
+                                price => special.price,
+                                data_attributes => data_attributes,
+                            }
+                        })
+                        .collect();
+                    Some(Value::from(specials))
+                }
+                _ => None,
+            },
+            "open_hours" => self
+                .restaurant
+                .open_hours
+                .as_ref()
+                .map(|open_hours| Value::from(open_hours.descriptions())),
+            "season" => match &self.restaurant.kind {
+                super::Kind::HappyHour {
+                    season: Some(season),
+                    ..
+                } => Some(Value::from(season.to_string())),
+                _ => None,
+            },
+            "in_season" => Some(Value::from(
+                self.restaurant.kind.in_season(self.options.today),
+            )),
+            "calendar" => (self.options.calendars
+                && matches!(self.restaurant.kind, super::Kind::HappyHour { .. }))
+            .then(|| Value::from(format!("calendar/{}.ics", self.restaurant.slug()))),
             "happytimes" => {
-                if let super::Kind::HappyHour { happytimes, .. } = &self.kind {
+                if let super::Kind::HappyHour { happytimes, .. } = &self.restaurant.kind {
+                    let slug = self.restaurant.slug();
+                    // Out of season listings match no day or time
+                    let in_season = self.restaurant.kind.in_season(self.options.today);
                     let human_times: Vec<_> = happytimes
-                        .as_human_readable()
+                        .as_human_readable(self.options.slot_minutes)
                         .into_iter()
-                        .map(|ht| {
+                        .enumerate()
+                        .map(|(index, ht)| {
+                            let data_attributes = if self.options.filter_index {
+                                format!(r#"data-filter="{slug}/{index}""#)
+                            } else if in_season {
+                                ht.data_attributes
+                            } else {
+                                NO_DAYTIMES.to_string()
+                            };
                             context! {
                                 description => ht.description,
-                                data_attributes => ht.data_attributes,
+                                data_attributes => data_attributes,
                             }
                         })
                         .collect();
+                    let data_attributes = if self.options.filter_index {
+                        format!(r#"data-filter="{slug}""#)
+                    } else if in_season {
+                        happytimes.as_data_attributes(self.options.slot_minutes)
+                    } else {
+                        NO_DAYTIMES.to_string()
+                    };
                     Some(context! {
-                        data_attributes => happytimes.as_data_attributes(),
+                        data_attributes => data_attributes,
                         times => human_times,
                     })
                 } else {
@@ -155,12 +261,21 @@ impl Object for super::Restaurant {
     fn enumerate(self: &Arc<Self>) -> Enumerator {
         Enumerator::Str(&[
             "name",
+            "slug",
             "url",
             "map_id",
             "instagram_id",
             "verified",
+            "days_since_verified",
+            "stale",
             "description",
             "menu_url",
+            "specials",
+            "open_hours",
+            "season",
+            "in_season",
+            "calendar",
+            "json_ld",
             "happytimes",
         ])
     }
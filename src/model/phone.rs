@@ -0,0 +1,133 @@
+use anyhow::{Result, anyhow};
+use phonenumber::{Mode, PhoneNumber, country};
+use serde::{Serialize, Serializer};
+
+use super::{Restaurant, Restaurants};
+
+/// A phone number as written in `restaurants.ron`, e.g. "732-555-4433 ext. 2" or "+44 20 7946 0958".
+/// Read as text, then parsed once the region it is local to is known, see `Restaurants::parse_phones`.
+#[derive(Clone, Debug)]
+pub struct Phone {
+    text: String,
+    number: PhoneNumber,
+}
+
+impl Phone {
+    /// Parse `text`, numbers without a country code are assumed to be in `region`.
+    pub(super) fn parse(text: &str, region: country::Id) -> Result<Self, String> {
+        let number = phonenumber::parse(Some(region), text)
+            .map_err(|err| format!("invalid phone {text:?}: {err}"))?;
+        Ok(Self {
+            text: text.to_owned(),
+            number,
+        })
+    }
+
+    /// As written in the data.
+    pub(super) fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// For display, national format for `region` and international format otherwise.
+    pub(super) fn display(&self, region: country::Id) -> String {
+        let mode = if self.number.country().id() == Some(region) {
+            Mode::National
+        } else {
+            Mode::International
+        };
+        self.number.format().mode(mode).to_string()
+    }
+
+    /// A `tel:` URL, including any extension.
+    pub(super) fn url(&self) -> String {
+        self.number.format().mode(Mode::Rfc3966).to_string()
+    }
+}
+
+impl AsRef<str> for Phone {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Restaurants<String> {
+    /// Parse every phone number for `region`, so invalid numbers are reported before anything is rendered.
+    pub fn parse_phones(self, region: country::Id) -> Result<Restaurants> {
+        let restaurants = self
+            .0
+            .into_iter()
+            .map(|restaurant| {
+                let phone = match &restaurant.phone {
+                    Some(text) => Some(
+                        Phone::parse(text, region)
+                            .map_err(|err| anyhow!("{}: {err}", restaurant.name))?,
+                    ),
+                    None => None,
+                };
+                Ok(Restaurant {
+                    name: restaurant.name,
+                    url: restaurant.url,
+                    phone,
+                    map_id: restaurant.map_id,
+                    instagram_id: restaurant.instagram_id,
+                    verified: restaurant.verified,
+                    open_hours: restaurant.open_hours,
+                    kind: restaurant.kind,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Restaurants(restaurants))
+    }
+}
+
+impl Serialize for Phone {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_phones() {
+        let parse = |phone: &str| {
+            let restaurants: Restaurants<String> = crate::ron_options()
+                .from_str(&format!(
+                    r#"[(
+    name: "The Black Swan",
+    url: "https://www.theblackswanap.com/",
+    phone: Some({phone:?}),
+    map_id: "a",
+    instagram_id: "",
+    verified: "2025-07-28",
+    kind: Byob,
+)]"#
+                ))
+                .unwrap();
+            let restaurants = restaurants.parse_phones(country::US)?;
+            let phone = restaurants.0[0].phone.clone().unwrap();
+            Ok::<_, anyhow::Error>((phone.display(country::US), phone.url()))
+        };
+        assert_eq!(
+            parse("732-555-4433").unwrap(),
+            ("(732) 555-4433".into(), "tel:+1-732-555-4433".into())
+        );
+        assert_eq!(
+            parse("732-555-4433 ext. 2").unwrap(),
+            (
+                "(732) 555-4433 ext. 2".into(),
+                "tel:+1-732-555-4433;ext=2".into()
+            )
+        );
+        assert_eq!(
+            parse("+44 20 7946 0958").unwrap(),
+            ("+44 20 7946 0958".into(), "tel:+44-20-7946-0958".into())
+        );
+        assert_eq!(
+            parse("call us").unwrap_err().to_string(),
+            r#"The Black Swan: invalid phone "call us": not a number"#
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt::Display;
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Part of the year a happy hour runs, e.g. `Summer` or `Between("2025-06-01", "2025-09-01")`.
+/// Dates are inclusive.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(super) enum Season {
+    /// Memorial Day through Labor Day
+    Summer,
+    Between(NaiveDate, NaiveDate),
+    From(NaiveDate),
+    Until(NaiveDate),
+}
+
+impl Season {
+    /// First and last day of the season in progress on `date`, or of the next one.
+    /// `None` where the season is open ended.
+    pub(super) fn dates(&self, date: NaiveDate) -> (Option<NaiveDate>, Option<NaiveDate>) {
+        match self {
+            Self::Summer => {
+                let year = if date > labor_day(date.year()) {
+                    date.year() + 1
+                } else {
+                    date.year()
+                };
+                (Some(memorial_day(year)), Some(labor_day(year)))
+            }
+            Self::Between(from, until) => (Some(*from), Some(*until)),
+            Self::From(from) => (Some(*from), None),
+            Self::Until(until) => (None, Some(*until)),
+        }
+    }
+
+    pub(super) fn contains(&self, date: NaiveDate) -> bool {
+        let (from, until) = self.dates(date);
+        from.is_none_or(|from| from <= date) && until.is_none_or(|until| date <= until)
+    }
+
+    /// Whether the season has ended for good by `date`.
+    pub(super) fn is_over(&self, date: NaiveDate) -> bool {
+        match self {
+            Self::Summer | Self::From(_) => false,
+            Self::Between(_, until) | Self::Until(until) => *until < date,
+        }
+    }
+}
+
+impl Display for Season {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const FORMAT: &str = "%b %-d, %Y";
+        match self {
+            Self::Summer => write!(f, "Memorial Day to Labor Day"),
+            Self::Between(from, until) => {
+                write!(f, "{} to {}", from.format(FORMAT), until.format(FORMAT))
+            }
+            Self::From(from) => write!(f, "from {}", from.format(FORMAT)),
+            Self::Until(until) => write!(f, "until {}", until.format(FORMAT)),
+        }
+    }
+}
+
+/// Last Monday in May.
+fn memorial_day(year: i32) -> NaiveDate {
+    let may_31 = NaiveDate::from_ymd_opt(year, 5, 31).expect("May 31");
+    may_31 - Days::new(u64::from(may_31.weekday().num_days_from_monday()))
+}
+
+/// First Monday in September.
+fn labor_day(year: i32) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, 9, Weekday::Mon, 1).expect("Labor Day")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_summer() {
+        assert_eq!(memorial_day(2025), date("2025-05-26"));
+        assert_eq!(labor_day(2025), date("2025-09-01"));
+        assert_eq!(memorial_day(2026), date("2026-05-25"));
+        assert_eq!(labor_day(2026), date("2026-09-07"));
+        assert!(!Season::Summer.contains(date("2025-05-25")));
+        assert!(Season::Summer.contains(date("2025-05-26")));
+        assert!(Season::Summer.contains(date("2025-09-01")));
+        assert!(!Season::Summer.contains(date("2025-09-02")));
+        assert_eq!(
+            Season::Summer.dates(date("2025-10-01")),
+            (Some(date("2026-05-25")), Some(date("2026-09-07")))
+        );
+        assert!(!Season::Summer.is_over(date("2025-10-01")));
+    }
+
+    #[test]
+    fn test_dates() {
+        let season = Season::Between(date("2025-06-01"), date("2025-08-31"));
+        assert!(season.contains(date("2025-06-01")));
+        assert!(season.contains(date("2025-08-31")));
+        assert!(!season.contains(date("2025-09-01")));
+        assert!(season.is_over(date("2025-09-01")));
+        assert!(Season::From(date("2025-06-01")).contains(date("2030-01-01")));
+        assert!(!Season::Until(date("2025-06-01")).contains(date("2025-06-02")));
+        assert_eq!(season.to_string(), "Jun 1, 2025 to Aug 31, 2025");
+        assert_eq!(
+            crate::ron_options().from_str::<Season>(r#"Between("2025-06-01", "2025-08-31")"#),
+            Ok(season)
+        );
+        assert_eq!(
+            crate::ron_options().from_str::<Season>("Summer"),
+            Ok(Season::Summer)
+        );
+    }
+}
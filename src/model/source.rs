@@ -0,0 +1,255 @@
+use ron::error::Position;
+
+/// Positions of the values in a RON document, used to point problems at the source.
+///
+/// This is a lenient scanner, not a parser, it assumes the document already deserialized.
+#[derive(Debug)]
+pub struct SourceNode {
+    pub position: Position,
+    fields: Vec<(String, SourceNode)>,
+    items: Vec<SourceNode>,
+}
+
+impl SourceNode {
+    pub fn parse(source: &str) -> Self {
+        let mut scanner = Scanner {
+            chars: source.chars().collect(),
+            index: 0,
+            line: 1,
+            col: 1,
+        };
+        scanner.value()
+    }
+
+    pub fn field(&self, name: &str) -> Option<&SourceNode> {
+        self.fields.iter().find(|(n, _)| n == name).map(|(_, n)| n)
+    }
+
+    pub fn item(&self, index: usize) -> Option<&SourceNode> {
+        self.items.get(index)
+    }
+}
+
+struct Scanner {
+    chars: Vec<char>,
+    index: usize,
+    line: usize,
+    col: usize,
+}
+
+impl Scanner {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.index + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.index += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        Some(c)
+    }
+
+    fn position(&self) -> Position {
+        Position {
+            line: self.line,
+            col: self.col,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match (self.peek(), self.peek_at(1)) {
+                (Some(c), _) if c.is_whitespace() => {
+                    self.bump();
+                }
+                (Some('/'), Some('/')) => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.bump();
+                    }
+                }
+                (Some('/'), Some('*')) => {
+                    self.bump();
+                    self.bump();
+                    while self.peek().is_some()
+                        && (self.peek(), self.peek_at(1)) != (Some('*'), Some('/'))
+                    {
+                        self.bump();
+                    }
+                    self.bump();
+                    self.bump();
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn value(&mut self) -> SourceNode {
+        self.skip_whitespace();
+        let mut node = SourceNode {
+            position: self.position(),
+            fields: Vec::new(),
+            items: Vec::new(),
+        };
+        match self.peek() {
+            Some('"') | Some('\'') => self.quoted(),
+            Some('[') => {
+                self.bump();
+                node.items = self.sequence(']');
+            }
+            Some('{') => {
+                self.bump();
+                // Map keys and values are scanned as a flat sequence
+                node.items = self.sequence('}');
+            }
+            Some('(') => {
+                self.bump();
+                self.group(&mut node);
+            }
+            Some(_) => {
+                self.word();
+                self.skip_whitespace();
+                if self.peek() == Some('(') {
+                    self.bump();
+                    self.group(&mut node);
+                }
+            }
+            None => {}
+        }
+        node
+    }
+
+    fn quoted(&mut self) {
+        let Some(quote) = self.bump() else {
+            return;
+        };
+        while let Some(c) = self.bump() {
+            if c == '\\' {
+                self.bump();
+            } else if c == quote {
+                break;
+            }
+        }
+    }
+
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '+' | '-'))
+        {
+            word.push(c);
+            self.bump();
+        }
+        if word.is_empty() {
+            // Skip anything unexpected so scanning always makes progress
+            self.bump();
+        }
+        word
+    }
+
+    fn sequence(&mut self, close: char) -> Vec<SourceNode> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => break,
+                Some(c) if c == close => {
+                    self.bump();
+                    break;
+                }
+                Some(',' | ':') => {
+                    self.bump();
+                }
+                Some(_) => items.push(self.value()),
+            }
+        }
+        items
+    }
+
+    /// Scan a parenthesized group, either struct fields or tuple items.
+    fn group(&mut self, node: &mut SourceNode) {
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => break,
+                Some(')') => {
+                    self.bump();
+                    break;
+                }
+                Some(',') => {
+                    self.bump();
+                }
+                Some(c) if c.is_alphabetic() || c == '_' => {
+                    let start = (self.index, self.line, self.col);
+                    let name = self.word();
+                    self.skip_whitespace();
+                    if self.peek() == Some(':') {
+                        self.bump();
+                        let value = self.value();
+                        node.fields.push((name, value));
+                    } else {
+                        (self.index, self.line, self.col) = start;
+                        node.items.push(self.value());
+                    }
+                }
+                Some(_) => node.items.push(self.value()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_positions() {
+        let source = r#"[
+    (
+        name: "A (b), c: \"d\"", // comment: (x
+        kind: HappyHour(
+            happytimes: [
+                Single(Mon, (1600, 1800)),
+                Range((Wed, Fri), (1600, 1800)),
+            ],
+        ),
+    ),
+    /* (skipped: [ */
+    (name: "B"),
+]"#;
+        let root = SourceNode::parse(source);
+        let pos = |node: Option<&SourceNode>| node.map(|n| (n.position.line, n.position.col));
+
+        assert_eq!(pos(root.item(0)), Some((2, 5)));
+        assert_eq!(
+            pos(root.item(0).and_then(|r| r.field("name"))),
+            Some((3, 15))
+        );
+        let happytimes = root
+            .item(0)
+            .and_then(|r| r.field("kind"))
+            .and_then(|k| k.field("happytimes"));
+        assert_eq!(pos(happytimes.and_then(|h| h.item(1))), Some((7, 17)));
+        assert_eq!(
+            pos(happytimes
+                .and_then(|h| h.item(1))
+                .and_then(|dh| dh.item(1))
+                .and_then(|hours| hours.item(1))),
+            Some((7, 42))
+        );
+        assert_eq!(
+            pos(root.item(1).and_then(|r| r.field("name"))),
+            Some((12, 12))
+        );
+        assert!(root.item(2).is_none());
+    }
+}
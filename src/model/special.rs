@@ -0,0 +1,131 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Day, HappyTimes, Hours, iter_days};
+
+/// A deal on particular days, e.g. `(days: Single(Wed), item: "2nd burger", price: Some("$5"))`.
+/// Specials without `hours` run during the happy hours on their days.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(super) struct Special {
+    pub(super) days: Days,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) hours: Option<Hours>,
+    pub(super) item: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) price: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub(super) enum Days {
+    Single(Day),
+    Range((Day, Day)),
+}
+
+impl Days {
+    fn range(&self) -> (Day, Day) {
+        match self {
+            Self::Single(day) => (*day, *day),
+            Self::Range(days) => *days,
+        }
+    }
+
+    pub(super) fn contains(&self, day: Day) -> bool {
+        iter_days(self.range()).any(|d| d == day)
+    }
+}
+
+impl Special {
+    /// When the special runs, e.g. "Wed" or "Mon-Fri 4pm-6pm".
+    pub(super) fn when(&self) -> String {
+        match &self.hours {
+            Some(hours) => format!("{} {hours}", self.days),
+            None => self.days.to_string(),
+        }
+    }
+
+    /// Each day and start minute of the filter slots the special is on,
+    /// its own hours or else those of `happytimes` on its days.
+    pub(super) fn as_tuples(&self, happytimes: &HappyTimes, slot_minutes: u16) -> Vec<(Day, u16)> {
+        match &self.hours {
+            Some(hours) => {
+                let slots = hours.slots(slot_minutes);
+                iter_days(self.days.range())
+                    .flat_map(|day| slots.clone().map(move |m| (day, m)))
+                    .collect()
+            }
+            None => happytimes
+                .0
+                .iter()
+                .flat_map(|dh| dh.as_tuples(slot_minutes))
+                .filter(|(day, _)| self.days.contains(*day))
+                .collect(),
+        }
+    }
+
+    pub(super) fn as_data_attributes(&self, happytimes: &HappyTimes, slot_minutes: u16) -> String {
+        let mut tuples = self.as_tuples(happytimes, slot_minutes);
+        tuples.sort();
+        tuples.dedup();
+        format!(
+            r#"data-daytimes="{}""#,
+            HappyTimes::data_daytimes(tuples.into_iter(), slot_minutes)
+        )
+    }
+}
+
+impl Display for Special {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.when(), self.item)?;
+        if let Some(price) = &self.price {
+            write!(f, " {price}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Days {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Single(day) => write!(f, "{day}"),
+            Self::Range((start, end)) => write!(f, "{start}-{end}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DayHours, HOUR_SLOT, Hour};
+
+    fn special(source: &str) -> Special {
+        crate::ron_options().from_str(source).unwrap()
+    }
+
+    #[test]
+    fn test_special() {
+        let happytimes = HappyTimes(vec![
+            DayHours::Single(Day::Mon, Hours(Hour(1600), Hour(1800))),
+            DayHours::Range((Day::Wed, Day::Fri), Hours(Hour(1600), Hour(1800))),
+        ]);
+        let burger = special(r#"(days: Single(Wed), item: "2nd burger", price: Some("$5"))"#);
+        assert_eq!(burger.to_string(), "Wed: 2nd burger $5");
+        assert_eq!(
+            burger.as_data_attributes(&happytimes, HOUR_SLOT),
+            r#"data-daytimes="3-16 3-17 3-all all-16 all-17""#
+        );
+        let oysters =
+            special(r#"(days: Range((Sun, Mon)), hours: Some((2000, 2130)), item: "$1 oysters")"#);
+        assert_eq!(oysters.to_string(), "Sun-Mon 8pm-9:30pm: $1 oysters");
+        assert_eq!(
+            oysters.as_data_attributes(&happytimes, HOUR_SLOT),
+            r#"data-daytimes="0-20 0-21 1-20 1-21 0-all 1-all all-20 all-21""#
+        );
+        // No happy hour on Tuesday
+        let tacos = special(r#"(days: Single(Tue), item: "Taco Tuesday")"#);
+        assert_eq!(
+            tacos.as_data_attributes(&happytimes, HOUR_SLOT),
+            r#"data-daytimes="""#
+        );
+    }
+}
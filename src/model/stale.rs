@@ -0,0 +1,111 @@
+use std::fmt::Display;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use super::Restaurants;
+
+const KIND_ORDER: [&str; 4] = ["HappyHour", "Byob", "Other", "Closed"];
+
+/// Restaurants due for re-verification, grouped by kind and oldest first.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct StaleReport(Vec<StaleGroup>);
+
+#[derive(Serialize, Debug, PartialEq)]
+struct StaleGroup {
+    kind: &'static str,
+    restaurants: Vec<StaleEntry>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct StaleEntry {
+    name: String,
+    verified: NaiveDate,
+    days_since_verified: i64,
+}
+
+impl StaleReport {
+    /// Restaurants last verified more than `older_than_days` before `today`.
+    pub fn new(restaurants: &Restaurants, today: NaiveDate, older_than_days: i64) -> Self {
+        let mut groups: Vec<StaleGroup> = Vec::new();
+        for restaurant in &restaurants.0 {
+            let days_since_verified = restaurant.days_since_verified(today);
+            if days_since_verified <= older_than_days {
+                continue;
+            }
+            let entry = StaleEntry {
+                name: restaurant.name.clone(),
+                verified: restaurant.verified,
+                days_since_verified,
+            };
+            let kind = restaurant.kind.name();
+            match groups.iter_mut().find(|group| group.kind == kind) {
+                Some(group) => group.restaurants.push(entry),
+                None => groups.push(StaleGroup {
+                    kind,
+                    restaurants: vec![entry],
+                }),
+            }
+        }
+        groups.sort_by_key(|group| KIND_ORDER.iter().position(|&kind| kind == group.kind));
+        for group in groups.iter_mut() {
+            group
+                .restaurants
+                .sort_by_key(|entry| (entry.verified, entry.name.clone()));
+        }
+        Self(groups)
+    }
+}
+
+impl Display for StaleReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for group in &self.0 {
+            writeln!(f, "{} ({})", group.kind, group.restaurants.len())?;
+            for entry in &group.restaurants {
+                writeln!(
+                    f,
+                    "  {:>4} days  {}  {}",
+                    entry.days_since_verified, entry.verified, entry.name
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::parse_restaurants;
+
+    #[test]
+    fn test_stale_report() {
+        let restaurants = parse_restaurants(include_str!("../../tests/site/_data/restaurants.ron"));
+        let report = StaleReport::new(
+            &restaurants,
+            NaiveDate::from_ymd_opt(2025, 9, 1).unwrap(),
+            180,
+        );
+        assert_eq!(
+            report.to_string(),
+            "Byob (3)
+   503 days  2024-04-16  Ada's Gojjo
+   503 days  2024-04-16  Dolce Fantasia
+   480 days  2024-05-09  Catbird Asbury Park
+Closed (1)
+   245 days  2024-12-30  Asbury Festhalle & Biergarten
+"
+        );
+        assert_eq!(
+            serde_json::to_value(&report).unwrap()[1],
+            serde_json::json!({
+                "kind": "Closed",
+                "restaurants": [{
+                    "name": "Asbury Festhalle & Biergarten",
+                    "verified": "2024-12-30",
+                    "days_since_verified": 245,
+                }],
+            })
+        );
+    }
+}
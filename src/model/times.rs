@@ -0,0 +1,640 @@
+use std::{cell::Cell, str::FromStr};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize, Serializer};
+
+use super::{
+    Day, DayHours, HappyTimes, Hour, Hours, Kind, Restaurant, Restaurants, Season, special::Days,
+};
+
+/// How happy hour times are written in `restaurants.ron`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TimesFormat {
+    /// e.g. `Range((Mon, Fri), (1600, 1800))`
+    #[default]
+    Tuples,
+    /// e.g. `"Mon-Fri 4pm-6pm"`
+    Text,
+}
+
+impl TimesFormat {
+    /// The format of a restaurants file, text if any happy, open or special hours are written
+    /// as text, the default if it cannot be read.
+    pub fn detect(source: &str) -> Self {
+        let Ok(restaurants) = crate::ron_options().from_str::<Vec<TimesRepr>>(source) else {
+            return Self::default();
+        };
+        let text = restaurants.iter().any(|restaurant| {
+            let (happytimes, specials) = match &restaurant.kind {
+                KindRepr::HappyHour {
+                    happytimes,
+                    specials,
+                } => (happytimes.as_slice(), specials.as_slice()),
+                _ => (&[][..], &[][..]),
+            };
+            restaurant
+                .open_hours
+                .iter()
+                .flatten()
+                .chain(happytimes)
+                .any(|dayhours| matches!(dayhours, DayHoursRepr::Text(_)))
+                || specials
+                    .iter()
+                    .any(|special| matches!(special.hours, Some(HoursRepr::Text(_))))
+        });
+        if text { Self::Text } else { Self::Tuples }
+    }
+}
+
+/// Just the times of a restaurant as written, to detect their format.
+#[derive(Deserialize)]
+struct TimesRepr {
+    #[serde(default)]
+    open_hours: Option<Vec<DayHoursRepr>>,
+    kind: KindRepr,
+}
+
+#[derive(Deserialize)]
+enum KindRepr {
+    Byob,
+    Other,
+    Closed,
+    HappyHour {
+        happytimes: Vec<DayHoursRepr>,
+        #[serde(default)]
+        specials: Vec<SpecialRepr>,
+    },
+}
+
+#[derive(Deserialize)]
+struct SpecialRepr {
+    #[serde(default)]
+    hours: Option<HoursRepr>,
+}
+
+impl<P: Serialize> Restaurants<P> {
+    /// Pretty printed RON, with times written in `format`.
+    pub fn to_ron(&self, format: TimesFormat) -> ron::Result<String> {
+        let restaurants: Vec<_> = self
+            .0
+            .iter()
+            .map(|restaurant| RestaurantRon::new(restaurant, format))
+            .collect();
+        crate::ron_options().to_string_pretty(&restaurants, crate::ron_pretty())
+    }
+}
+
+/// A `Restaurant` serialized as its derived `Serialize` does, except for times written in a
+/// `TimesFormat`. Keep the fields in step with `Restaurant` and `Kind`.
+#[derive(Serialize)]
+struct RestaurantRon<'a, P> {
+    name: &'a str,
+    url: &'a str,
+    phone: &'a Option<P>,
+    map_id: &'a str,
+    instagram_id: &'a str,
+    verified: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    open_hours: Option<Times<'a>>,
+    kind: KindRon<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "Kind")]
+enum KindRon<'a> {
+    Byob,
+    Other,
+    Closed,
+    HappyHour {
+        description: &'a [String],
+        menu_url: &'a Option<String>,
+        happytimes: Times<'a>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        specials: Vec<SpecialRon<'a>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        season: Option<&'a Season>,
+    },
+}
+
+/// A `Special` serialized as its derived `Serialize` does, with its hours written in a
+/// `TimesFormat`. Keep the fields in step with `Special`.
+#[derive(Serialize)]
+#[serde(rename = "Special")]
+struct SpecialRon<'a> {
+    days: Days,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hours: Option<SpecialHours<'a>>,
+    item: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    price: &'a Option<String>,
+}
+
+/// Happy or open hours, written in `TimesFormat`.
+struct Times<'a>(&'a HappyTimes, TimesFormat);
+
+/// The hours of a special, written in `TimesFormat`.
+struct SpecialHours<'a>(&'a Hours, TimesFormat);
+
+impl<'a, P> RestaurantRon<'a, P> {
+    fn new(restaurant: &'a Restaurant<P>, format: TimesFormat) -> Self {
+        let kind = match &restaurant.kind {
+            Kind::Byob => KindRon::Byob,
+            Kind::Other => KindRon::Other,
+            Kind::Closed => KindRon::Closed,
+            Kind::HappyHour {
+                description,
+                menu_url,
+                happytimes,
+                specials,
+                season,
+            } => KindRon::HappyHour {
+                description,
+                menu_url,
+                happytimes: Times(happytimes, format),
+                specials: specials
+                    .iter()
+                    .map(|special| SpecialRon {
+                        days: special.days,
+                        hours: special
+                            .hours
+                            .as_ref()
+                            .map(|hours| SpecialHours(hours, format)),
+                        item: &special.item,
+                        price: &special.price,
+                    })
+                    .collect(),
+                season: season.as_ref(),
+            },
+        };
+        Self {
+            name: &restaurant.name,
+            url: &restaurant.url,
+            phone: &restaurant.phone,
+            map_id: &restaurant.map_id,
+            instagram_id: &restaurant.instagram_id,
+            verified: restaurant.verified,
+            open_hours: restaurant
+                .open_hours
+                .as_ref()
+                .map(|open_hours| Times(open_hours, format)),
+            kind,
+        }
+    }
+}
+
+impl Serialize for Times<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.1 {
+            TimesFormat::Tuples => self.0.serialize(serializer),
+            TimesFormat::Text => serializer.collect_seq(self.0.0.iter().map(DayHours::to_string)),
+        }
+    }
+}
+
+impl Serialize for SpecialHours<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.1 {
+            TimesFormat::Tuples => self.0.serialize(serializer),
+            TimesFormat::Text => serializer.serialize_str(&self.0.to_string()),
+        }
+    }
+}
+
+/// `Hours` as written in data files, e.g. `(1600, 1830)` or `"4pm-6:30pm"`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(super) enum HoursRepr {
+    Text(String),
+    Tuple((u16, u16)),
+}
+
+impl TryFrom<HoursRepr> for Hours {
+    type Error = String;
+
+    fn try_from(repr: HoursRepr) -> Result<Self, Self::Error> {
+        match repr {
+            HoursRepr::Text(s) => s.parse(),
+            HoursRepr::Tuple((start, end)) => Ok(Hours(start.try_into()?, end.try_into()?)),
+        }
+    }
+}
+
+/// `DayHours` as written in data files, hours are validated after deserializing.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub(super) enum DayHoursRepr {
+    Text(String),
+    Tuple(DayHoursTuple),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "DayHours")]
+pub(super) enum DayHoursTuple {
+    Single(Day, (u16, u16)),
+    Range((Day, Day), (u16, u16)),
+}
+
+impl TryFrom<DayHoursRepr> for DayHours {
+    type Error = String;
+
+    fn try_from(repr: DayHoursRepr) -> Result<Self, Self::Error> {
+        let hours =
+            |(start, end): (u16, u16)| Ok::<_, String>(Hours(start.try_into()?, end.try_into()?));
+        match repr {
+            DayHoursRepr::Text(s) => s.parse(),
+            DayHoursRepr::Tuple(DayHoursTuple::Single(day, h)) => {
+                Ok(DayHours::Single(day, hours(h)?))
+            }
+            DayHoursRepr::Tuple(DayHoursTuple::Range(days, h)) => {
+                Ok(DayHours::Range(days, hours(h)?))
+            }
+        }
+    }
+}
+
+impl Serialize for DayHours {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let hours = self.hours();
+        let hours = (hours.0.0, hours.1.0);
+        match *self {
+            DayHours::Single(day, _) => DayHoursTuple::Single(day, hours),
+            DayHours::Range(days, _) => DayHoursTuple::Range(days, hours),
+        }
+        .serialize(serializer)
+    }
+}
+
+thread_local! {
+    static LENIENT_HOURS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `f` accepting any hour, so `check` can read the data and report invalid hours
+/// with its other problems, see `Hour::validate`.
+pub(super) fn lenient_hours<T>(f: impl FnOnce() -> T) -> T {
+    let lenient = LENIENT_HOURS.replace(true);
+    let result = f();
+    LENIENT_HOURS.set(lenient);
+    result
+}
+
+impl Hour {
+    /// Whether this is a valid hour, as checked by `try_from` outside of `lenient_hours`.
+    pub(super) fn validate(self) -> Result<(), String> {
+        let hour = self.0;
+        if hour > Hours::END_HOUR * 100 || hour % 100 > 59 {
+            return Err(format!(
+                "invalid hour {hour}, expected e.g. 1630, up to {} with minutes between 0 and 59",
+                Hours::END_HOUR * 100
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<u16> for Hour {
+    type Error = String;
+
+    /// An hour written as e.g. 1630, up to 2500 for 1am the next day.
+    fn try_from(hour: u16) -> Result<Self, Self::Error> {
+        let hour = Self(hour);
+        if !LENIENT_HOURS.get() {
+            hour.validate()?;
+        }
+        Ok(hour)
+    }
+}
+
+impl FromStr for DayHours {
+    type Err = String;
+
+    /// Parse times as displayed, e.g. "Mon-Fri 4pm-6:30pm" or "Sat 11pm-1am".
+    /// The am/pm of the start may be left off, e.g. "Mon-Fri 4-6pm".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (days, hours) = s
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| format!("{s:?}: expected days and hours, e.g. \"Mon-Fri 4pm-6pm\""))?;
+        let hours = hours
+            .trim()
+            .parse::<Hours>()
+            .map_err(|err| format!("{s:?}: {err}"))?;
+        match days.split_once('-') {
+            Some((start, end)) => {
+                let start = start.parse().map_err(|err| format!("{s:?}: {err}"))?;
+                let end = end.parse().map_err(|err| format!("{s:?}: {err}"))?;
+                Ok(DayHours::Range((start, end), hours))
+            }
+            None => Ok(DayHours::Single(
+                days.parse().map_err(|err| format!("{s:?}: {err}"))?,
+                hours,
+            )),
+        }
+    }
+}
+
+impl FromStr for Day {
+    type Err = String;
+
+    /// A day abbreviated as displayed, or written out in full, in any case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_ascii_lowercase();
+        Day::iter()
+            .find(|day| {
+                let name = day.to_string().to_ascii_lowercase();
+                lower == name || (lower.starts_with(&name) && full_name(*day) == lower)
+            })
+            .ok_or_else(|| format!("unknown day {s:?}, expected e.g. Mon"))
+    }
+}
+
+fn full_name(day: Day) -> &'static str {
+    match day {
+        Day::Sun => "sunday",
+        Day::Mon => "monday",
+        Day::Tue => "tuesday",
+        Day::Wed => "wednesday",
+        Day::Thu => "thursday",
+        Day::Fri => "friday",
+        Day::Sat => "saturday",
+    }
+}
+
+impl FromStr for Hours {
+    type Err = String;
+
+    /// A time range such as "4pm-6:30pm", ending after midnight for e.g. "11pm-1am".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| format!("expected a time range like 4pm-6pm, not {s:?}"))?;
+        let start = Time::parse(start)?;
+        let end = Time::parse(end)?;
+        let (start, end) = match (start.meridiem, end.meridiem) {
+            (None, None) if start.hour <= 12 && end.hour <= 12 => {
+                return Err(format!("add am or pm to {s:?}"));
+            }
+            // "4-6pm" shares the end's am/pm, unless that puts the start after the end as in "11-2pm"
+            (None, Some(meridiem)) => {
+                let end = end.minutes();
+                let same = start.with(meridiem).minutes();
+                let start = if same < end {
+                    same
+                } else {
+                    start.with(meridiem.opposite()).minutes()
+                };
+                (start, end)
+            }
+            (Some(meridiem), None) => (start.minutes(), end.with(meridiem).minutes()),
+            _ => (start.minutes(), end.minutes()),
+        };
+        // Times past midnight continue the same day, e.g. 1am is 2500
+        let end = if end <= start { end + 24 * 60 } else { end };
+        if end > Hours::END_HOUR as u32 * 60 {
+            return Err(format!(
+                "{s:?} ends too late, happy hours must end by {}",
+                Hour(Hours::END_HOUR * 100)
+            ));
+        }
+        Ok(Hours(hour(start), hour(end)))
+    }
+}
+
+fn hour(minutes: u32) -> Hour {
+    Hour((minutes / 60 * 100 + minutes % 60) as u16)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Meridiem {
+    Am,
+    Pm,
+}
+
+impl Meridiem {
+    fn opposite(self) -> Self {
+        match self {
+            Self::Am => Self::Pm,
+            Self::Pm => Self::Am,
+        }
+    }
+}
+
+/// A time of day as written, e.g. "4", "6:30pm" or "16:00".
+struct Time {
+    hour: u32,
+    minute: u32,
+    meridiem: Option<Meridiem>,
+}
+
+impl Time {
+    fn parse(s: &str) -> Result<Self, String> {
+        let lower = s.trim().to_ascii_lowercase();
+        let (digits, meridiem) = if let Some(digits) = lower.strip_suffix("am") {
+            (digits, Some(Meridiem::Am))
+        } else if let Some(digits) = lower.strip_suffix("pm") {
+            (digits, Some(Meridiem::Pm))
+        } else {
+            (lower.as_str(), None)
+        };
+        let invalid = || format!("invalid time {:?}, expected e.g. 4pm or 6:30pm", s.trim());
+        let (hour, minute) = match digits.trim().split_once(':') {
+            Some((hour, minute)) if minute.len() == 2 => (hour, minute),
+            Some(_) => return Err(invalid()),
+            None => (digits.trim(), "0"),
+        };
+        let hour: u32 = hour.parse().map_err(|_| invalid())?;
+        let minute: u32 = minute.parse().map_err(|_| invalid())?;
+        let limit = if meridiem.is_some() { 1..=12 } else { 0..=23 };
+        if !limit.contains(&hour) || minute > 59 {
+            return Err(invalid());
+        }
+        Ok(Self {
+            hour,
+            minute,
+            meridiem,
+        })
+    }
+
+    fn with(&self, meridiem: Meridiem) -> Self {
+        Self {
+            meridiem: Some(meridiem),
+            ..*self
+        }
+    }
+
+    /// Minutes past midnight.
+    fn minutes(&self) -> u32 {
+        let hour = match self.meridiem {
+            None => self.hour,
+            Some(Meridiem::Am) => self.hour % 12,
+            Some(Meridiem::Pm) => self.hour % 12 + 12,
+        };
+        hour * 60 + self.minute
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<String, String> {
+        s.parse::<DayHours>().map(|dh| format!("{dh:?}"))
+    }
+
+    #[test]
+    fn test_parse_dayhours() {
+        assert_eq!(
+            parse("Mon-Fri 4pm-6:30pm"),
+            Ok("Range((Mon, Fri), Hours(Hour(1600), Hour(1830)))".into())
+        );
+        assert_eq!(
+            parse("mon-fri 4-6pm"),
+            Ok("Range((Mon, Fri), Hours(Hour(1600), Hour(1800)))".into())
+        );
+        assert_eq!(
+            parse("Sunday 11-2pm"),
+            Ok("Single(Sun, Hours(Hour(1100), Hour(1400)))".into())
+        );
+        assert_eq!(
+            parse("Fri 11pm-1am"),
+            Ok("Single(Fri, Hours(Hour(2300), Hour(2500)))".into())
+        );
+        assert_eq!(
+            parse("Fri 10pm-12am"),
+            Ok("Single(Fri, Hours(Hour(2200), Hour(2400)))".into())
+        );
+        assert_eq!(
+            parse("Sat 16:00-18:30"),
+            Ok("Single(Sat, Hours(Hour(1600), Hour(1830)))".into())
+        );
+        assert_eq!(
+            parse("Sun 1am-4am"),
+            Ok("Single(Sun, Hours(Hour(100), Hour(400)))".into())
+        );
+    }
+
+    #[test]
+    fn test_parse_display_roundtrip() {
+        for s in [
+            "Mon-Fri 4pm-6:30pm",
+            "Sat 11pm-1am",
+            "Sun 12pm-12am",
+            "Tue 9am-5pm",
+        ] {
+            assert_eq!(s.parse::<DayHours>().unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_times_format() {
+        let tuples = r#"[
+    (
+        name: "Swan",
+        url: "https://example.com/",
+        phone: None,
+        map_id: "a",
+        instagram_id: "",
+        verified: "2025-01-01",
+        kind: HappyHour(
+            description: [],
+            menu_url: None,
+            happytimes: [
+                Range((Mon, Fri), (1600, 1830)),
+                Single(Sat, (2300, 2500)),
+            ],
+        ),
+    ),
+]"#;
+        let text = tuples
+            .replace("Range((Mon, Fri), (1600, 1830))", r#""Mon-Fri 4pm-6:30pm""#)
+            .replace("Single(Sat, (2300, 2500))", r#""Sat 11pm-1am""#);
+        assert_eq!(TimesFormat::detect(tuples), TimesFormat::Tuples);
+        assert_eq!(TimesFormat::detect(&text), TimesFormat::Text);
+        // Only the times themselves count, wherever they are
+        let open_hours = tuples.replace(
+            "    kind: HappyHour(",
+            "    open_hours: Some([\"Tue-Sun 11am-9pm\"]),\n        kind: HappyHour(",
+        );
+        assert_eq!(TimesFormat::detect(&open_hours), TimesFormat::Text);
+        let description = tuples.replace(
+            "description: []",
+            r#"description: ["happytimes: [\"all day\"]"]"#,
+        );
+        assert_eq!(TimesFormat::detect(&description), TimesFormat::Tuples);
+        // Special hours too, which are written in the same form as the others
+        let specials = tuples.replace(
+            "menu_url: None,",
+            r#"menu_url: None, specials: [(days: Single(Sun), hours: Some("5pm-7pm"), item: "Wings")],"#,
+        );
+        assert_eq!(TimesFormat::detect(&specials), TimesFormat::Text);
+        let restaurants: Restaurants<String> = crate::ron_options().from_str(&specials).unwrap();
+        let ron = restaurants.to_ron(TimesFormat::Tuples).unwrap();
+        assert!(ron.contains("hours: Some((1700, 1900))"), "{ron}");
+        assert_eq!(TimesFormat::detect(&ron), TimesFormat::Tuples);
+        let ron = restaurants.to_ron(TimesFormat::Text).unwrap();
+        assert!(ron.contains(r#"hours: Some("5pm-7pm")"#), "{ron}");
+
+        // Either form reads the same, and can be written in either form
+        let restaurants: Restaurants<String> = crate::ron_options().from_str(&text).unwrap();
+        assert_eq!(restaurants.to_ron(TimesFormat::Tuples).unwrap(), tuples);
+        assert_eq!(restaurants.to_ron(TimesFormat::Text).unwrap(), text);
+        let restaurants: Restaurants<String> = crate::ron_options().from_str(tuples).unwrap();
+        assert_eq!(restaurants.to_ron(TimesFormat::Text).unwrap(), text);
+        // Tuples are written as by the derived `Serialize`
+        let site: Restaurants<String> = crate::ron_options()
+            .from_str(include_str!("../../tests/site/_data/restaurants.ron"))
+            .unwrap();
+        assert_eq!(
+            site.to_ron(TimesFormat::Tuples).unwrap(),
+            crate::ron_options()
+                .to_string_pretty(&site, crate::ron_pretty())
+                .unwrap()
+        );
+        // JSON output keeps the tuple form
+        assert_eq!(
+            serde_json::to_value(&restaurants).unwrap()[0]["kind"]["HappyHour"]["happytimes"][1],
+            serde_json::json!({"Single": ["Sat", [2300, 2500]]})
+        );
+    }
+
+    #[test]
+    fn test_deserialize_errors() {
+        let error = |happytimes: &str| {
+            crate::ron_options()
+                .from_str::<Vec<DayHours>>(happytimes)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            error(r#"["Mon-Fri 4pm-6pm", "Sat 11pm-3am"]"#),
+            r#"1:22-1:35: "Sat 11pm-3am": "11pm-3am" ends too late, happy hours must end by 1am"#
+        );
+        assert_eq!(
+            error("[Single(Sat, (2300, 2700))]"),
+            "1:26-1:27: invalid hour 2700, expected e.g. 1630, up to 2500 with minutes between 0 and 59"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("Mon"),
+            Err(r#""Mon": expected days and hours, e.g. "Mon-Fri 4pm-6pm""#.into())
+        );
+        assert_eq!(
+            parse("Mun 4pm-6pm"),
+            Err(r#""Mun 4pm-6pm": unknown day "Mun", expected e.g. Mon"#.into())
+        );
+        assert_eq!(
+            parse("Mon 4-6"),
+            Err(r#""Mon 4-6": add am or pm to "4-6""#.into())
+        );
+        assert_eq!(
+            parse("Mon 4:3pm-6pm"),
+            Err(r#""Mon 4:3pm-6pm": invalid time "4:3pm", expected e.g. 4pm or 6:30pm"#.into())
+        );
+        assert_eq!(
+            parse("Sat 11pm-3am"),
+            Err(r#""Sat 11pm-3am": "11pm-3am" ends too late, happy hours must end by 1am"#.into())
+        );
+    }
+}
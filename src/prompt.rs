@@ -0,0 +1,105 @@
+use anyhow::{Result, anyhow};
+use std::io::{BufRead, Write};
+
+/// Asks questions one line at a time, on the terminal or any reader and writer.
+pub struct Prompter<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    /// The reader and writer, e.g. to inspect what was written.
+    pub fn into_inner(self) -> (R, W) {
+        (self.input, self.output)
+    }
+
+    /// Ask for a single line, an empty answer gives `default` if there is one.
+    pub fn ask(&mut self, question: &str, default: Option<&str>) -> Result<String> {
+        loop {
+            match default {
+                Some(default) if !default.is_empty() => {
+                    write!(self.output, "{question} [{default}]: ")?
+                }
+                _ => write!(self.output, "{question}: ")?,
+            }
+            self.output.flush()?;
+            let answer = self.read_line()?;
+            match (answer.is_empty(), default) {
+                (true, Some(default)) => return Ok(default.to_owned()),
+                (true, None) => writeln!(self.output, "  An answer is required")?,
+                (false, _) => return Ok(answer),
+            }
+        }
+    }
+
+    /// Ask until the answer parses, reporting each error.
+    pub fn ask_parsed<T>(
+        &mut self,
+        question: &str,
+        default: Option<&str>,
+        parse: impl Fn(&str) -> Result<T, String>,
+    ) -> Result<T> {
+        loop {
+            let answer = self.ask(question, default)?;
+            match parse(&answer) {
+                Ok(value) => return Ok(value),
+                Err(err) => writeln!(self.output, "  {err}")?,
+            }
+        }
+    }
+
+    /// Ask for a list, one entry per line until an empty line.
+    /// An empty list keeps `current` and `-` alone clears it, each entry is checked with `parse`.
+    pub fn ask_list<T>(
+        &mut self,
+        question: &str,
+        current: &[String],
+        parse: impl Fn(&str) -> Result<T, String>,
+    ) -> Result<Vec<T>> {
+        writeln!(
+            self.output,
+            "{question}, one per line, end with an empty line:"
+        )?;
+        for entry in current {
+            writeln!(self.output, "  current: {entry}")?;
+        }
+        if !current.is_empty() {
+            writeln!(self.output, "  an empty line keeps these, - clears them")?;
+        }
+        let mut entries = Vec::new();
+        loop {
+            write!(self.output, "> ")?;
+            self.output.flush()?;
+            let line = self.read_line()?;
+            if line.is_empty() {
+                break;
+            }
+            if line == "-" && entries.is_empty() {
+                return Ok(Vec::new());
+            }
+            match parse(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(err) => writeln!(self.output, "  {err}")?,
+            }
+        }
+        if entries.is_empty() {
+            return current
+                .iter()
+                .map(|entry| parse(entry).map_err(|err| anyhow!(err)))
+                .collect();
+        }
+        Ok(entries)
+    }
+
+    fn read_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Err(anyhow!("Unexpected end of input"));
+        }
+        Ok(line.trim().to_owned())
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::{Result, anyhow};
+use std::{
+    fs,
+    net::{SocketAddr, ToSocketAddrs},
+    path::{Component, Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Polled by the injected script, returns the current build generation.
+const LIVE_RELOAD_PATH: &str = "/_livereload";
+
+const LIVE_RELOAD_SCRIPT: &str = r#"<script>
+(() => {
+    let generation = null;
+    setInterval(async () => {
+        try {
+            const current = await (await fetch("/_livereload")).text();
+            if (generation !== null && current !== generation) {
+                location.reload();
+            }
+            generation = current;
+        } catch {}
+    }, 500);
+})();
+</script>
+"#;
+
+/// Local HTTP server for a built site, reloading browsers after each rebuild.
+pub struct PreviewServer {
+    http: Server,
+    root: PathBuf,
+    generation: Arc<AtomicU64>,
+}
+
+/// Handle used to tell browsers connected to a [`PreviewServer`] to reload.
+#[derive(Clone)]
+pub struct Reloader(Arc<AtomicU64>);
+
+impl Reloader {
+    pub fn reload(&self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl PreviewServer {
+    pub fn bind(addr: impl ToSocketAddrs, root: impl AsRef<Path>) -> Result<Self> {
+        let http = Server::http(addr).map_err(|err| anyhow!("Failed to start server: {err}"))?;
+        Ok(Self {
+            http,
+            root: root.as_ref().to_owned(),
+            generation: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    pub fn addr(&self) -> Option<SocketAddr> {
+        self.http.server_addr().to_ip()
+    }
+
+    pub fn reloader(&self) -> Reloader {
+        Reloader(self.generation.clone())
+    }
+
+    /// Serve requests until [`PreviewServer::shutdown`] is called.
+    pub fn run(&self) {
+        for request in self.http.incoming_requests() {
+            if let Err(err) = self.respond(request) {
+                eprintln!("Request failed: {err:?}");
+            }
+        }
+    }
+
+    pub fn shutdown(&self) {
+        self.http.unblock();
+    }
+
+    fn respond(&self, request: Request) -> Result<()> {
+        if request.method() != &Method::Get && request.method() != &Method::Head {
+            return Ok(request.respond(Response::empty(405))?);
+        }
+        let url_path = request.url().split(['?', '#']).next().unwrap_or("/");
+        if url_path == LIVE_RELOAD_PATH {
+            let generation = self.generation.load(Ordering::SeqCst).to_string();
+            return Ok(request.respond(
+                Response::from_string(generation).with_header(header("Cache-Control", "no-store")),
+            )?);
+        }
+
+        let Some(path) = self.resolve(url_path) else {
+            return Ok(request.respond(Response::from_string("Not Found").with_status_code(404))?);
+        };
+        let content_type = content_type(&path);
+        let mut body = fs::read(&path)?;
+        if content_type.starts_with("text/html") {
+            inject_live_reload(&mut body);
+        }
+        Ok(request.respond(
+            Response::from_data(body)
+                .with_header(header("Content-Type", content_type))
+                .with_header(header("Cache-Control", "no-store")),
+        )?)
+    }
+
+    /// Map a URL path to a file under the root, rejecting anything that escapes it.
+    fn resolve(&self, url_path: &str) -> Option<PathBuf> {
+        let decoded = percent_decode(url_path)?;
+        let relative = Path::new(decoded.trim_start_matches('/'));
+        if relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            return None;
+        }
+        let mut path = self.root.join(relative);
+        if path.is_dir() {
+            path.push("index.html");
+        }
+        path.is_file().then_some(path)
+    }
+}
+
+fn inject_live_reload(body: &mut Vec<u8>) {
+    let position = body
+        .windows(7)
+        .rposition(|w| w.eq_ignore_ascii_case(b"</body>"))
+        .unwrap_or(body.len());
+    body.splice(position..position, LIVE_RELOAD_SCRIPT.bytes());
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(b);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("json") => "application/json",
+        Some("svg") => "image/svg+xml",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("png") => "image/png",
+        Some("txt") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name, value).expect("valid header")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inject_live_reload() {
+        let mut body = b"<html><body>hi</body></html>".to_vec();
+        inject_live_reload(&mut body);
+        let body = String::from_utf8(body).unwrap();
+        assert!(body.starts_with("<html><body>hi<script>"));
+        assert!(body.ends_with("</script>\n</body></html>"));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("index.html"), "").unwrap();
+        fs::create_dir(root.path().join("images")).unwrap();
+        fs::write(root.path().join("images/a b.svg"), "").unwrap();
+        let server = PreviewServer::bind("127.0.0.1:0", root.path()).unwrap();
+
+        assert_eq!(server.resolve("/"), Some(root.path().join("index.html")));
+        assert_eq!(
+            server.resolve("/images/a%20b.svg"),
+            Some(root.path().join("images/a b.svg"))
+        );
+        assert_eq!(server.resolve("/missing.html"), None);
+        assert_eq!(server.resolve("/../index.html"), None);
+        assert_eq!(server.resolve("/images/%2e%2e/index.html"), None);
+    }
+}
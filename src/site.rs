@@ -1,96 +1,590 @@
 use crate::{
-    model::{Restaurants, restaurants_value},
-    ron_options,
+    config::{CONFIG_FILE, Config},
+    diagnostic::{Frame, TemplateError, TemplateErrors},
+    feed::{self, FeedEntry},
+    front_matter::FrontMatter,
+    model::{
+        Change, FILTER_INDEX_FILE, RenderOptions, Restaurants, TimesFormat, add_template_functions,
+        restaurant_values, restaurants_value,
+    },
+    ron_options, sitemap,
 };
 use anyhow::{Context, Result, anyhow};
-use minijinja::{AutoEscape, Environment, context};
+use chrono::{DateTime, NaiveDate, Utc};
+use minijinja::{AutoEscape, Environment, Value, context};
 use std::{
+    collections::{BTreeMap, HashMap, HashSet},
     fs::{self, OpenOptions},
+    io::Write,
     path::{Path, PathBuf},
 };
 
+/// Marks a template rendered once per restaurant, replaced by the restaurant slug.
+const SLUG_PLACEHOLDER: &str = "[slug]";
+
+/// Top level entries of a site that are read to build it rather than copied to the output.
+const SITE_SOURCES: [&str; 3] = ["_data", "_templates", CONFIG_FILE];
+
+/// Names in the template context that front matter `vars` may not replace.
+const CONTEXT_NAMES: [&str; 5] = ["site", "restaurants", "data", "restaurant", "root"];
+
 pub struct SiteGenerator<'a> {
     jinja: Environment<'a>,
+    front_matter: BTreeMap<String, FrontMatter>,
+    config: Config,
     restaurants: Restaurants,
+    data: BTreeMap<String, Value>,
+    options: RenderOptions,
+    /// Build date from `with_today`, otherwise the current date is used
+    today: Option<NaiveDate>,
+    sitemap: bool,
+    clean: bool,
     site: PathBuf,
 }
 
 impl<'a> SiteGenerator<'a> {
     pub fn new(site: impl AsRef<Path>) -> Result<Self> {
         let site = site.as_ref();
-        let ronpath = site.join("_data/restaurants.ron");
-        let restaurants = fs::File::open(&ronpath).context(format!("{ronpath:?}"))?;
-        let restaurants: Restaurants = ron_options().from_reader(restaurants)?;
 
         let mut jinja = Environment::new();
         jinja.set_auto_escape_callback(|_| AutoEscape::None);
         jinja.set_lstrip_blocks(true);
         jinja.set_trim_blocks(true);
+        // Keep template source in errors for diagnostics, also in release builds
+        jinja.set_debug(true);
+
+        let config = Config::load(site)?;
+        let restaurants = load_restaurants(site, config.phone_region)?;
+        let options = RenderOptions {
+            today: config.today(),
+            phone_region: config.phone_region,
+            slot_minutes: config.slot_minutes,
+            filter_index: config.filter_index,
+            ..Default::default()
+        };
+        let mut generator = Self {
+            jinja,
+            front_matter: BTreeMap::new(),
+            config,
+            restaurants,
+            data: load_data(site)?,
+            options,
+            today: None,
+            sitemap: false,
+            clean: true,
+            site: site.to_owned(),
+        };
+        generator.reload_templates()?;
+        add_template_functions(&mut generator.jinja, &generator.options);
+        Ok(generator)
+    }
+
+    /// Render as if built on `today`, instead of the current date.
+    pub fn with_today(mut self, today: NaiveDate) -> Self {
+        self.today = Some(today);
+        self.options.today = today;
+        add_template_functions(&mut self.jinja, &self.options);
+        self
+    }
+
+    /// Also write iCalendar feeds of the happy hours under `calendar/`.
+    pub fn with_calendars(mut self, calendars: bool) -> Self {
+        self.options.calendars = calendars;
+        self
+    }
+
+    /// Also write an Atom feed of changes since the restaurant data previously written to the output.
+    pub fn with_feed(mut self, feed: bool) -> Self {
+        self.options.feed = feed;
+        self
+    }
+
+    /// Also write `sitemap.xml` and `robots.txt` after building.
+    pub fn with_sitemap(mut self, sitemap: bool) -> Self {
+        self.sitemap = sitemap;
+        self
+    }
+
+    /// Remove files left in the output by previous builds (the default), instead of keeping them
+    /// by copying the previous output into staging before building.
+    pub fn with_clean(mut self, clean: bool) -> Self {
+        self.clean = clean;
+        self
+    }
+
+    pub fn site(&self) -> &Path {
+        &self.site
+    }
 
-        let templates = &site.join("_templates");
+    /// Re-parse `_config.ron` and everything under `_data`, keeping the current data if it fails to load.
+    pub fn reload_data(&mut self) -> Result<()> {
+        let config = Config::load(&self.site)?;
+        let restaurants = load_restaurants(&self.site, config.phone_region)?;
+        self.data = load_data(&self.site)?;
+        self.options.today = self.today.unwrap_or_else(|| config.today());
+        self.options.phone_region = config.phone_region;
+        self.options.slot_minutes = config.slot_minutes;
+        self.options.filter_index = config.filter_index;
+        self.config = config;
+        self.restaurants = restaurants;
+        add_template_functions(&mut self.jinja, &self.options);
+        Ok(())
+    }
+
+    /// Re-read every template under `_templates`, along with its front matter.
+    /// Front matter and syntax errors are reported for all templates at once as `TemplateErrors`.
+    pub fn reload_templates(&mut self) -> Result<()> {
+        self.jinja.clear_templates();
+        self.front_matter.clear();
+        let templates = &self.site.join("_templates");
+        let mut errors = Vec::new();
         visit_files(templates, &mut |path: &Path| -> Result<()> {
-            let filename = path.strip_prefix(templates)?;
-            jinja.add_template_owned(
-                filename
-                    .to_str()
-                    .ok_or(anyhow!("Invalid filename"))?
-                    .to_owned(),
-                fs::read_to_string(path)?,
-            )?;
+            let name = path
+                .strip_prefix(templates)?
+                .to_str()
+                .ok_or(anyhow!("Invalid filename"))?
+                .to_owned();
+            let text = fs::read_to_string(path)?;
+            let mut error = |message: String, position, label: &str| {
+                errors.push(TemplateError {
+                    frames: Frame::in_source(&name, &text, position, label)
+                        .into_iter()
+                        .collect(),
+                    template: name.clone(),
+                    output: None,
+                    message,
+                })
+            };
+            let (front_matter, source) = match FrontMatter::parse(&text) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    error(
+                        err.message,
+                        (err.line, err.column, err.width),
+                        "invalid front matter",
+                    );
+                    return Ok(());
+                }
+            };
+            if let Some(var) = front_matter
+                .vars
+                .keys()
+                .find(|var| CONTEXT_NAMES.contains(&var.as_str()))
+            {
+                error(
+                    format!("front matter var {var:?} is reserved"),
+                    locate(&text, &format!("{var:?}")),
+                    "reserved",
+                );
+                return Ok(());
+            }
+            if let Some(output) = &front_matter.output
+                && !is_relative_path(output)
+            {
+                error(
+                    format!(
+                        "front matter output {output:?} must be a relative path without . or .."
+                    ),
+                    locate(&text, &format!("{output:?}")),
+                    "invalid output",
+                );
+                return Ok(());
+            }
+            match self.jinja.add_template_owned(name.clone(), source) {
+                Ok(()) => {
+                    self.front_matter.insert(name, front_matter);
+                }
+                Err(err) => errors.push(TemplateError::new(&err, &name, None)),
+            }
             Ok(())
         })?;
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            errors.sort_by(|a, b| a.template.cmp(&b.template));
+            Err(TemplateErrors(errors).into())
+        }
+    }
 
-        Ok(Self {
-            jinja,
-            restaurants,
-            site: site.to_owned(),
-        })
+    /// Templates that are rendered, and the output path of each which may contain `[slug]`.
+    fn outputs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.front_matter
+            .iter()
+            .filter(|(_, front_matter)| front_matter.render)
+            .map(|(name, front_matter)| {
+                (
+                    name.as_str(),
+                    front_matter.output.as_deref().unwrap_or(name),
+                )
+            })
+    }
+
+    /// Build the whole site into a staging directory next to `output`, then swap it into place.
+    /// A failed build leaves the previous output untouched.
+    pub fn build(&self, output: impl AsRef<Path>) -> Result<()> {
+        let output = std::path::absolute(output)?;
+        let staging = sibling_dir(&output, "staging")?;
+        // Left behind if a previous build was interrupted
+        remove_dir(&staging)?;
+        if !self.clean && output.is_dir() {
+            copy_dir(&output, &staging)?;
+        }
+        fs::create_dir_all(&staging)
+            .with_context(|| format!("create staging {}", staging.display()))?;
+        if let Err(err) = self.build_into(&staging, &output) {
+            remove_dir(&staging)?;
+            return Err(err);
+        }
+        replace_dir(&staging, &output)
     }
 
-    pub fn build(self, output: impl AsRef<Path>) -> Result<()> {
+    fn build_into(&self, staging: &Path, previous: &Path) -> Result<()> {
+        self.write_data_since(staging, previous)?;
+        self.render(staging)?;
+        self.copy_static(staging)?;
+        self.write_sitemap(staging)
+    }
+
+    /// Write the restaurant data as JSON, and the filter index and iCalendar feeds if enabled.
+    pub fn write_data(&self, output: impl AsRef<Path>) -> Result<()> {
         let output = output.as_ref();
+        self.write_data_since(output, output)
+    }
 
-        let json_path = output.join("restaurant.json");
-        create_parent_dirs(&json_path)
-            .with_context(|| format!("create parents {}", json_path.display()))?;
-        let json_file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&json_path)
-            .with_context(|| format!("open failed {}", json_path.display()))?;
+    /// Write the data to `output`, with the feed listing changes since the build in `previous`.
+    fn write_data_since(&self, output: &Path, previous: &Path) -> Result<()> {
+        if self.options.feed {
+            self.write_feed(output, previous)?;
+        }
+        let json_file = create_file(output.join("restaurant.json"))?;
         serde_json::to_writer_pretty(json_file, &self.restaurants)?;
 
-        let context = context! { restaurants => restaurants_value(self.restaurants) };
-
-        for (name, template) in self.jinja.templates() {
-            let output_path = output.join(name);
-            create_parent_dirs(&output_path)
-                .with_context(|| format!("create parents {}", output_path.display()))?;
-            let f = OpenOptions::new()
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(&output_path)
-                .with_context(|| format!("open failed {}", output_path.display()))?;
-            if let Err(err) = template.render_to_write(&context, f) {
-                eprintln!("Render failed: {err:?}");
-                return Err(anyhow!("Render failed"));
+        if self.options.filter_index {
+            let index = self
+                .restaurants
+                .filter_index(self.options.slot_minutes, self.options.today)?;
+            serde_json::to_writer(create_file(output.join(FILTER_INDEX_FILE))?, &index)?;
+        }
+
+        if self.options.calendars {
+            let today = self.options.today;
+            let tz = self.config.timezone;
+            let calendar = output.join("calendar");
+            for (slug, ics) in self.restaurants.restaurant_calendars(today, tz) {
+                create_file(calendar.join(format!("{slug}.ics")))?.write_all(ics.as_bytes())?;
             }
+            create_file(calendar.join("happy-hours.ics"))?
+                .write_all(self.restaurants.calendar(today, tz).as_bytes())?;
         }
+        Ok(())
+    }
 
-        visit_files(&self.site, &mut |path: &Path| -> Result<()> {
-            let filename = path.strip_prefix(&self.site)?;
-            if filename.starts_with("_templates") || filename.starts_with("_data") {
+    /// Add changes since the data in the `previous` build to its feed history, and write the feed.
+    fn write_feed(&self, output: &Path, previous: &Path) -> Result<()> {
+        let today = self.options.today;
+        let mut history = feed::load_history(&previous.join(feed::HISTORY_FILE))?;
+        let previous_json = &previous.join("restaurant.json");
+        if previous_json.exists() {
+            let previous: Restaurants<String> =
+                serde_json::from_reader(fs::File::open(previous_json)?)
+                    .with_context(|| format!("{}", previous_json.display()))?;
+            let page = self
+                .outputs()
+                .map(|(_, output)| output)
+                .find(|output| output.contains(SLUG_PLACEHOLDER));
+            let base_url = self.config.base_url.trim_end_matches('/');
+            let entries = self
+                .restaurants
+                .changes_since(&previous)
+                .iter()
+                .map(|change| {
+                    let link = match page {
+                        Some(page) if !matches!(change, Change::Removed { .. }) => {
+                            format!(
+                                "{base_url}/{}",
+                                page.replace(SLUG_PLACEHOLDER, change.slug())
+                            )
+                        }
+                        _ => format!("{base_url}/"),
+                    };
+                    FeedEntry::new(change, today, &self.config, link)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            feed::add_entries(&mut history, entries);
+        }
+        serde_json::to_writer_pretty(create_file(output.join(feed::HISTORY_FILE))?, &history)?;
+        create_file(output.join(feed::FEED_FILE))?
+            .write_all(feed::atom(&self.config, &history, today)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Render every template to the output path matching its name, or the `output` in its front matter.
+    /// Templates with `[slug]` in their output path are rendered once per restaurant,
+    /// with that restaurant available as `restaurant`. Templates with `render: false` are skipped.
+    /// Every template is rendered up to its first error, and the errors are returned as `TemplateErrors`.
+    pub fn render(&self, output: impl AsRef<Path>) -> Result<()> {
+        let output = output.as_ref();
+        let restaurants = restaurants_value(self.restaurants.clone(), self.options.clone());
+        let data = Value::from_iter(self.data.clone());
+
+        let mut errors = Vec::new();
+        let mut rendered_from = HashMap::new();
+        for (name, page_path) in self.outputs() {
+            let template = self.jinja.get_template(name)?;
+            let vars = Value::from_serialize(&self.front_matter[name].vars);
+            let pages = if page_path.contains(SLUG_PLACEHOLDER) {
+                self.restaurant_pages(page_path)?
+            } else {
+                vec![(page_path.to_owned(), Value::UNDEFINED)]
+            };
+            for (path, restaurant) in pages {
+                if let Some(other) = rendered_from.insert(PathBuf::from(&path), name)
+                    && other != name
+                {
+                    errors.push(TemplateError {
+                        template: name.to_owned(),
+                        output: Some(path.clone()),
+                        message: format!("output {path} is also rendered from {other}"),
+                        frames: Vec::new(),
+                    });
+                    break;
+                }
+                let context = context! {
+                    site => Value::from_serialize(&self.config),
+                    restaurants => restaurants.clone(),
+                    data => data.clone(),
+                    restaurant => restaurant,
+                    root => relative_root(&path),
+                    ..vars.clone()
+                };
+                match template.render(&context) {
+                    Ok(rendered) => {
+                        create_file(output.join(&path))?.write_all(rendered.as_bytes())?
+                    }
+                    Err(err) => {
+                        errors.push(TemplateError::new(&err, name, Some(&path)));
+                        break;
+                    }
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(TemplateErrors(errors).into())
+        }
+    }
+
+    /// Output paths and values for a collection template rendered once per restaurant.
+    fn restaurant_pages(&self, name: &str) -> Result<Vec<(String, Value)>> {
+        let mut seen = HashSet::new();
+        restaurant_values(&self.restaurants, self.options.clone())
+            .into_iter()
+            .map(|(slug, restaurant)| {
+                if !seen.insert(slug.clone()) {
+                    return Err(anyhow!("Duplicate restaurant slug {slug:?} for {name}"));
+                }
+                Ok((name.replace(SLUG_PLACEHOLDER, &slug), restaurant))
+            })
+            .collect()
+    }
+
+    /// Write `sitemap.xml` listing every file in the output, and a `robots.txt` pointing at it,
+    /// unless the site provides its own. Does nothing unless enabled.
+    pub fn write_sitemap(&self, output: impl AsRef<Path>) -> Result<()> {
+        if !self.sitemap {
+            return Ok(());
+        }
+        let output = output.as_ref();
+        let dates = self.output_dates();
+        let mut entries = Vec::new();
+        visit_files(output, &mut |path: &Path| -> Result<()> {
+            let name = path
+                .strip_prefix(output)?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if name == "sitemap.xml" || name == "robots.txt" {
                 return Ok(());
             }
-            let dest = output.join(filename);
-            copy_path(path, dest)?;
+            let lastmod = match dates.get(&name) {
+                Some(date) => *date,
+                None => self.modified_date(&name, path)?,
+            };
+            entries.push((name, lastmod));
             Ok(())
         })?;
+        entries.sort();
 
+        create_file(output.join("sitemap.xml"))?
+            .write_all(sitemap::sitemap(&self.config.base_url, &entries)?.as_bytes())?;
+        if !self.site.join("robots.txt").exists() {
+            create_file(output.join("robots.txt"))?
+                .write_all(sitemap::robots(&self.config.base_url)?.as_bytes())?;
+        }
         Ok(())
     }
+
+    /// Last change of each generated output, the newest `verified` date of the restaurants on it.
+    fn output_dates(&self) -> HashMap<String, NaiveDate> {
+        let verified = self.restaurants.verified_dates();
+        let Some(newest) = verified.iter().map(|(_, date)| *date).max() else {
+            return HashMap::new();
+        };
+        let mut dates = HashMap::from([
+            ("restaurant.json".to_owned(), newest),
+            ("calendar/happy-hours.ics".to_owned(), newest),
+        ]);
+        for (slug, date) in &verified {
+            dates.insert(format!("calendar/{slug}.ics"), *date);
+        }
+        for (_, output) in self.outputs() {
+            if output.contains(SLUG_PLACEHOLDER) {
+                for (slug, date) in &verified {
+                    dates.insert(output.replace(SLUG_PLACEHOLDER, slug), *date);
+                }
+            } else {
+                dates.insert(output.to_owned(), newest);
+            }
+        }
+        dates
+    }
+
+    /// Modification date of a static file, from its source in the site if it has one.
+    fn modified_date(&self, name: &str, output_path: &Path) -> Result<NaiveDate> {
+        let source = self.site.join(name);
+        let path = if source.is_file() {
+            &source
+        } else {
+            output_path
+        };
+        let modified: DateTime<Utc> = fs::metadata(path)?.modified()?.into();
+        Ok(modified.with_timezone(&self.config.timezone).date_naive())
+    }
+
+    /// Copy all static files, see `is_static`.
+    pub fn copy_static(&self, output: impl AsRef<Path>) -> Result<()> {
+        let output = output.as_ref();
+        visit_files(&self.site, &mut |path: &Path| -> Result<()> {
+            if self.is_static(path) {
+                self.copy_static_file(path, output)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Copy a single static file to the same path under the output.
+    pub fn copy_static_file(&self, path: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        copy_path(path, output.as_ref().join(path.strip_prefix(&self.site)?))
+    }
+
+    /// Whether `path` is a static file copied verbatim to the output,
+    /// anything in the site except `_data`, `_templates` and `_config.ron`.
+    pub fn is_static(&self, path: impl AsRef<Path>) -> bool {
+        path.as_ref()
+            .strip_prefix(&self.site)
+            .is_ok_and(|filename| !SITE_SOURCES.iter().any(|name| filename.starts_with(name)))
+    }
+}
+
+/// Write restaurants to a RON file, formatted as by `ronfmt`.
+pub fn write_restaurants(
+    ronpath: &Path,
+    restaurants: &Restaurants,
+    format: TimesFormat,
+) -> Result<()> {
+    fs::write(ronpath, restaurants.to_ron(format)?)
+        .with_context(|| format!("{}", ronpath.display()))
+}
+
+/// Position and width of the first occurrence of `needle` in a template `source`,
+/// or the start of the template if it does not occur.
+fn locate(source: &str, needle: &str) -> (usize, usize, usize) {
+    source
+        .lines()
+        .enumerate()
+        .find_map(|(index, line)| {
+            let column = line[..line.find(needle)?].chars().count() + 1;
+            Some((index + 1, column, needle.chars().count()))
+        })
+        .unwrap_or((1, 1, 1))
+}
+
+/// Whether `path` stays within the directory it is relative to, e.g. "about/index.html".
+fn is_relative_path(path: &str) -> bool {
+    let path = Path::new(path);
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, std::path::Component::Normal(_)))
+}
+
+/// Load `_data/restaurants.ron` from the site directory,
+/// parsing phone numbers without a country code as local to `phone_region`.
+pub fn load_restaurants(
+    site: &Path,
+    phone_region: phonenumber::country::Id,
+) -> Result<Restaurants> {
+    let ronpath = site.join("_data/restaurants.ron");
+    read_restaurants(&ronpath)?
+        .parse_phones(phone_region)
+        .with_context(|| format!("{}", ronpath.display()))
+}
+
+/// Load restaurants from a RON file, with phone numbers as written.
+pub fn read_restaurants(ronpath: &Path) -> Result<Restaurants<String>> {
+    let restaurants = fs::File::open(ronpath).context(format!("{ronpath:?}"))?;
+    ron_options()
+        .from_reader(restaurants)
+        .with_context(|| format!("{}", ronpath.display()))
+}
+
+/// Load every other `.ron`, `.json` and `.toml` file under `_data`, keyed by file stem.
+fn load_data(site: &Path) -> Result<BTreeMap<String, Value>> {
+    let mut data = BTreeMap::new();
+    visit_files(&site.join("_data"), &mut |path: &Path| -> Result<()> {
+        let (Some(stem), Some(extension)) = (
+            path.file_stem().and_then(|s| s.to_str()),
+            path.extension().and_then(|s| s.to_str()),
+        ) else {
+            return Ok(());
+        };
+        if stem == "restaurants" && extension == "ron" {
+            return Ok(());
+        }
+        let source = || fs::read_to_string(path).with_context(|| format!("{}", path.display()));
+        let value = match extension {
+            "ron" => Value::from_serialize(
+                ron_options()
+                    .from_str::<ron::Value>(&source()?)
+                    .with_context(|| format!("{}", path.display()))?,
+            ),
+            "json" => Value::from_serialize(
+                serde_json::from_str::<serde_json::Value>(&source()?)
+                    .with_context(|| format!("{}", path.display()))?,
+            ),
+            "toml" => Value::from_serialize(
+                toml::from_str::<toml::Table>(&source()?)
+                    .with_context(|| format!("{}", path.display()))?,
+            ),
+            _ => return Ok(()),
+        };
+        if data.insert(stem.to_owned(), value).is_some() {
+            return Err(anyhow!(
+                "Duplicate data file {stem:?} in {}",
+                path.display()
+            ));
+        }
+        Ok(())
+    })?;
+    Ok(data)
+}
+
+/// Prefix that leads from an output path back to the site root, e.g. `../` for `restaurant/a.html`.
+fn relative_root(path: &str) -> String {
+    "../".repeat(Path::new(path).components().count().saturating_sub(1))
 }
 
 fn visit_files<F>(dir: &Path, cb: &mut F) -> Result<()>
@@ -111,6 +605,81 @@ where
     Ok(())
 }
 
+/// A directory next to `dir` for use while building it, e.g. `.output.staging` for `output`.
+pub(crate) fn sibling_dir(dir: &Path, suffix: &str) -> Result<PathBuf> {
+    let name = dir
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid output directory {}", dir.display()))?;
+    Ok(dir.with_file_name(format!(".{}.{suffix}", name.to_string_lossy())))
+}
+
+/// Replace `dir` with `staging`, swapping them in one step where the filesystem supports it.
+/// Otherwise the old contents are moved aside, and moved back if the new ones cannot be put in place.
+fn replace_dir(staging: &Path, dir: &Path) -> Result<()> {
+    let rename = |from: &Path, to: &Path| {
+        fs::rename(from, to)
+            .with_context(|| format!("rename {} to {}", from.display(), to.display()))
+    };
+    if !dir.exists() {
+        return rename(staging, dir);
+    }
+    if exchange(staging, dir).is_ok() {
+        // Staging now holds the previous output
+        return remove_dir(staging);
+    }
+    let old = sibling_dir(dir, "old")?;
+    remove_dir(&old)?;
+    rename(dir, &old)?;
+    if let Err(err) = rename(staging, dir) {
+        rename(&old, dir).context("restore previous output")?;
+        return Err(err);
+    }
+    remove_dir(&old)
+}
+
+/// Atomically swap two paths.
+#[cfg(target_os = "linux")]
+fn exchange(a: &Path, b: &Path) -> std::io::Result<()> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+    let a = CString::new(a.as_os_str().as_bytes())?;
+    let b = CString::new(b.as_os_str().as_bytes())?;
+    // SAFETY: both paths are NUL terminated and outlive the call
+    let result = unsafe {
+        libc::renameat2(
+            libc::AT_FDCWD,
+            a.as_ptr(),
+            libc::AT_FDCWD,
+            b.as_ptr(),
+            libc::RENAME_EXCHANGE,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn exchange(_a: &Path, _b: &Path) -> std::io::Result<()> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+fn remove_dir(dir: &Path) -> Result<()> {
+    match fs::remove_dir_all(dir) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+            Err(err).with_context(|| format!("remove {}", dir.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    visit_files(from, &mut |path: &Path| {
+        copy_path(path, to.join(path.strip_prefix(from)?))
+    })
+}
+
 fn create_parent_dirs(path: impl AsRef<Path>) -> Result<()> {
     let path = path.as_ref();
     if let Some(parent) = path.parent() {
@@ -119,9 +688,264 @@ fn create_parent_dirs(path: impl AsRef<Path>) -> Result<()> {
     Ok(())
 }
 
+fn create_file(path: impl AsRef<Path>) -> Result<fs::File> {
+    let path = path.as_ref();
+    create_parent_dirs(path).with_context(|| format!("create parents {}", path.display()))?;
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .with_context(|| format!("open failed {}", path.display()))
+}
+
 fn copy_path<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
     let to = to.as_ref();
     create_parent_dirs(to)?;
     fs::copy(from, to)?;
     Ok(())
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_data() {
+        let site = tempfile::tempdir().unwrap();
+        let data = site.path().join("_data");
+        fs::create_dir(&data).unwrap();
+        fs::write(data.join("restaurants.ron"), "[]").unwrap();
+        fs::write(
+            data.join("site.ron"),
+            r#"(title: "Happy Hours", tags: ["a", "b"])"#,
+        )
+        .unwrap();
+        fs::write(data.join("events.json"), r#"[{"name": "Fireworks"}]"#).unwrap();
+        fs::write(data.join("links.toml"), "form = \"https://example.com/\"").unwrap();
+        fs::write(data.join("notes.txt"), "ignored").unwrap();
+
+        let data = load_data(site.path()).unwrap();
+        assert_eq!(
+            data.keys().collect::<Vec<_>>(),
+            vec!["events", "links", "site"]
+        );
+        let env = Environment::new();
+        let render = |source| {
+            env.render_str(source, context! { data => Value::from_iter(data.clone()) })
+                .unwrap()
+        };
+        assert_eq!(
+            render("{{ data.site.title }} {{ data.site.tags | join(',') }}"),
+            "Happy Hours a,b"
+        );
+        assert_eq!(render("{{ data.events[0].name }}"), "Fireworks");
+        assert_eq!(render("{{ data.links.form }}"), "https://example.com/");
+
+        fs::write(site.path().join("_data/site.json"), "{}").unwrap();
+        assert!(load_data(site.path()).is_err());
+    }
+
+    /// A site with the test config, no restaurants and an empty `_templates`.
+    pub(crate) fn empty_site() -> tempfile::TempDir {
+        let site = tempfile::tempdir().unwrap();
+        fs::create_dir_all(site.path().join("_data")).unwrap();
+        fs::create_dir_all(site.path().join("_templates")).unwrap();
+        fs::copy(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/site/_config.ron"),
+            site.path().join(crate::CONFIG_FILE),
+        )
+        .unwrap();
+        fs::write(site.path().join("_data/restaurants.ron"), "[]").unwrap();
+        site
+    }
+
+    #[test]
+    fn test_render_front_matter() {
+        let site = empty_site();
+        let site = site.path();
+        fs::write(
+            site.join("_templates/layout.html"),
+            "---\n(render: false)\n---\n<title>{% block title %}{% endblock %}</title>\n",
+        )
+        .unwrap();
+        fs::write(
+            site.join("_templates/about.html"),
+            r#"---
+(output: Some("about/index.html"), vars: {"title": "About"})
+---
+{% extends "layout.html" %}
+{% block title %}{{ title }} {{ site.title }} {{ root }}{% endblock %}
+"#,
+        )
+        .unwrap();
+
+        let output = tempfile::tempdir().unwrap();
+        let output = output.path();
+        SiteGenerator::new(site).unwrap().render(output).unwrap();
+        assert_eq!(
+            fs::read_to_string(output.join("about/index.html")).unwrap(),
+            "<title>About Asbury Park Happy Hours ../</title>"
+        );
+        assert!(!output.join("about.html").exists());
+        assert!(!output.join("layout.html").exists());
+
+        fs::write(
+            site.join("_templates/about.html"),
+            "---\n(vars: {\"site\": 1})\n---\n",
+        )
+        .unwrap();
+        assert!(SiteGenerator::new(site).is_err());
+
+        // Outputs must stay within the output directory, and not collide
+        let output_to = |path: &str| {
+            fs::write(
+                site.join("_templates/about.html"),
+                format!("---\n(output: Some({path:?}))\n---\n"),
+            )
+            .unwrap();
+            SiteGenerator::new(site).and_then(|generator| generator.render(output))
+        };
+        assert!(output_to("../about.html").is_err());
+        assert!(output_to("/tmp/about.html").is_err());
+        assert!(output_to("about/../../about.html").is_err());
+        fs::write(site.join("_templates/index.html"), "index").unwrap();
+        let err = output_to("index.html").unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TemplateErrors>().unwrap().0[0].message,
+            "output index.html is also rendered from about.html"
+        );
+        assert!(output_to("about/index.html").is_ok());
+    }
+
+    #[test]
+    fn test_reload_data_today() {
+        let site = empty_site();
+        let mut generator = SiteGenerator::new(site.path()).unwrap();
+        // As if the site had been watched since an earlier day
+        generator.options.today = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
+        generator.reload_data().unwrap();
+        assert_eq!(generator.options.today, generator.config.today());
+
+        let today = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
+        let mut generator = generator.with_today(today);
+        generator.reload_data().unwrap();
+        assert_eq!(generator.options.today, today);
+    }
+
+    #[test]
+    fn test_replace_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let (staging, dir) = (root.path().join(".out.staging"), root.path().join("out"));
+        for (path, contents) in [(&staging, "new"), (&dir, "old")] {
+            fs::create_dir(path).unwrap();
+            fs::write(path.join("index.html"), contents).unwrap();
+        }
+        replace_dir(&staging, &dir).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("index.html")).unwrap(), "new");
+        assert!(!staging.exists());
+        assert_eq!(fs::read_dir(root.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_copy_static_file() {
+        let site = empty_site();
+        let site = site.path();
+        let output = tempfile::tempdir().unwrap();
+        let output = output.path();
+        let generator = SiteGenerator::new(site).unwrap();
+        let images = site.join("images");
+        fs::create_dir(&images).unwrap();
+        fs::write(images.join("menu.svg"), "<svg/>").unwrap();
+        generator
+            .copy_static_file(images.join("menu.svg"), output)
+            .unwrap();
+        assert!(output.join("images/menu.svg").is_file());
+
+        // Only the generator's own files are left out, not everything starting with _
+        assert!(generator.is_static(site.join("_headers")));
+        assert!(!generator.is_static(site.join("_data/restaurants.ron")));
+        assert!(!generator.is_static(site.join(crate::CONFIG_FILE)));
+    }
+
+    #[test]
+    fn test_template_errors() {
+        let site = empty_site();
+        let site = site.path();
+        let templates = site.join("_templates");
+        fs::write(templates.join("a.html"), "{{ 1 + 'a' }}").unwrap();
+        fs::write(templates.join("b.html"), "\n{{ missing.attr }}").unwrap();
+        fs::write(templates.join("ok.html"), "ok").unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let err = SiteGenerator::new(site)
+            .unwrap()
+            .render(output.path())
+            .unwrap_err();
+        let errors = &err.downcast_ref::<TemplateErrors>().unwrap().0;
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| (e.template.as_str(), e.frames[0].line))
+                .collect::<Vec<_>>(),
+            vec![("a.html", 1), ("b.html", 2)]
+        );
+        assert!(err.to_string().ends_with("2 template(s) failed"));
+        assert!(output.path().join("ok.html").exists());
+
+        fs::write(templates.join("a.html"), "{% if %}").unwrap();
+        fs::write(templates.join("b.html"), "{% endfor %}").unwrap();
+        fs::write(templates.join("c.html"), "---\n(render: maybe)\n---\n").unwrap();
+        fs::write(
+            templates.join("d.html"),
+            "---\n(vars: {\"site\": 1})\n---\n",
+        )
+        .unwrap();
+        let err = SiteGenerator::new(site).err().unwrap();
+        let errors = &err.downcast_ref::<TemplateErrors>().unwrap().0;
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| (e.template.as_str(), e.frames[0].line))
+                .collect::<Vec<_>>(),
+            vec![("a.html", 1), ("b.html", 1), ("c.html", 2), ("d.html", 2)]
+        );
+        assert_eq!(errors[3].frames[0].column, 9);
+    }
+
+    #[test]
+    fn test_build_staging() {
+        let site = empty_site();
+        let site = site.path();
+        let page = site.join("_templates/index.html");
+        let root = tempfile::tempdir().unwrap();
+        let output = root.path().join("output");
+        let build = |clean| {
+            SiteGenerator::new(site)
+                .and_then(|generator| generator.with_clean(clean).build(&output))
+        };
+
+        fs::write(&page, "v1").unwrap();
+        build(false).unwrap();
+        fs::write(output.join("stale.html"), "").unwrap();
+
+        // A failed render leaves the previous build in place
+        fs::write(&page, "v2 {{ missing.attr }}").unwrap();
+        assert!(build(false).is_err());
+        assert_eq!(fs::read_to_string(output.join("index.html")).unwrap(), "v1");
+        assert_eq!(
+            fs::read_dir(root.path()).unwrap().count(),
+            1,
+            "staging directory left behind"
+        );
+
+        fs::write(&page, "v2").unwrap();
+        build(false).unwrap();
+        assert_eq!(fs::read_to_string(output.join("index.html")).unwrap(), "v2");
+        assert!(output.join("stale.html").exists());
+
+        build(true).unwrap();
+        assert!(!output.join("stale.html").exists());
+        assert!(output.join("restaurant.json").exists());
+        assert_eq!(fs::read_dir(root.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::fmt::Write;
+use url::Url;
+
+/// A sitemap listing every output path under `base_url`, with the date each last changed.
+pub fn sitemap(base_url: &str, entries: &[(String, NaiveDate)]) -> Result<String> {
+    let base = site_url(base_url)?;
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for (path, lastmod) in entries {
+        // Link to directories rather than their index page
+        let path = match path.strip_suffix("index.html") {
+            Some(dir) if dir.is_empty() || dir.ends_with('/') => dir,
+            _ => path,
+        };
+        writeln!(xml, "  <url>")?;
+        writeln!(xml, "    <loc>{}</loc>", escape(base.join(path)?.as_str()))?;
+        writeln!(xml, "    <lastmod>{lastmod}</lastmod>")?;
+        writeln!(xml, "  </url>")?;
+    }
+    xml.push_str("</urlset>\n");
+    Ok(xml)
+}
+
+/// A robots.txt allowing everything and pointing crawlers at the sitemap.
+pub fn robots(base_url: &str) -> Result<String> {
+    let sitemap = site_url(base_url)?.join("sitemap.xml")?;
+    Ok(format!("User-agent: *\nAllow: /\n\nSitemap: {sitemap}\n"))
+}
+
+/// `base_url` as a directory, so relative paths resolve beneath it.
+fn site_url(base_url: &str) -> Result<Url> {
+    Ok(Url::parse(&format!("{}/", base_url.trim_end_matches('/')))?)
+}
+
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 8, day).unwrap()
+    }
+
+    #[test]
+    fn test_sitemap() {
+        let entries = vec![
+            ("index.html".to_owned(), date(28)),
+            ("images/a&b.svg".to_owned(), date(1)),
+            ("restaurant/the-black-swan.html".to_owned(), date(2)),
+        ];
+        assert_eq!(
+            sitemap("https://example.com/guide", &entries).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/guide/</loc>
+    <lastmod>2025-08-28</lastmod>
+  </url>
+  <url>
+    <loc>https://example.com/guide/images/a&amp;b.svg</loc>
+    <lastmod>2025-08-01</lastmod>
+  </url>
+  <url>
+    <loc>https://example.com/guide/restaurant/the-black-swan.html</loc>
+    <lastmod>2025-08-02</lastmod>
+  </url>
+</urlset>
+"#
+        );
+    }
+
+    #[test]
+    fn test_robots() {
+        assert_eq!(
+            robots("https://example.com/").unwrap(),
+            "User-agent: *\nAllow: /\n\nSitemap: https://example.com/sitemap.xml\n"
+        );
+    }
+}
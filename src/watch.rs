@@ -0,0 +1,183 @@
+use crate::{CONFIG_FILE, SiteGenerator, site::sibling_dir};
+use anyhow::Result;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+/// How long to keep collecting events after the first one, editors often write several files.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// What needs rebuilding after a batch of filesystem events.
+#[derive(Default, Debug, PartialEq)]
+struct Changes {
+    data: bool,
+    templates: bool,
+    files: BTreeSet<PathBuf>,
+}
+
+impl Changes {
+    /// Note a change to `path` under `site`, the absolute path of the generator's site.
+    fn add(&mut self, generator: &SiteGenerator, site: &Path, output: &Path, path: &Path) {
+        // Builds write to staging next to the output, and may move the old output aside
+        let build_dir = |suffix| sibling_dir(output, suffix).is_ok_and(|dir| path.starts_with(dir));
+        if path.starts_with(output) || build_dir("staging") || build_dir("old") {
+            return;
+        }
+        let Ok(filename) = path.strip_prefix(site) else {
+            return;
+        };
+        if filename.starts_with("_data") || filename == Path::new(CONFIG_FILE) {
+            self.data = true;
+        } else if filename.starts_with("_templates") {
+            self.templates = true;
+        } else if generator.is_static(generator.site().join(filename)) && !path.is_dir() {
+            self.files.insert(path.to_owned());
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.data && !self.templates && self.files.is_empty()
+    }
+}
+
+/// Build the site, then rebuild it each time a file under the site changes.
+/// `on_rebuild` is called after each successful rebuild.
+pub fn watch(
+    generator: &mut SiteGenerator,
+    output: impl AsRef<Path>,
+    mut on_rebuild: impl FnMut(),
+) -> Result<()> {
+    let output = output.as_ref();
+    generator.build(output)?;
+    eprintln!("Watching {}", generator.site().display());
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(generator.site(), RecursiveMode::Recursive)?;
+
+    // Event paths are rooted at the watched path, compare output the same way
+    let output = std::path::absolute(output)?;
+    let site = std::path::absolute(generator.site())?;
+
+    while let Ok(event) = rx.recv() {
+        let mut changes = Changes::default();
+        let mut pending = Some(event);
+        while let Some(event) = pending {
+            let event = event?;
+            // Reading files during a rebuild generates access events, ignore them
+            if !matches!(event.kind, EventKind::Access(_)) {
+                for path in event.paths {
+                    let path = std::path::absolute(&path)?;
+                    changes.add(generator, &site, &output, &path);
+                }
+            }
+            pending = rx.recv_timeout(DEBOUNCE).ok();
+        }
+        if changes.is_empty() {
+            continue;
+        }
+        match rebuild(generator, &output, &changes) {
+            Ok(()) => on_rebuild(),
+            Err(err) => eprintln!("Rebuild failed: {err:?}"),
+        }
+    }
+    Ok(())
+}
+
+/// Reload whatever changed, then build into staging like `build`,
+/// so a failed rebuild leaves the previous output in place.
+fn rebuild(generator: &mut SiteGenerator, output: &Path, changes: &Changes) -> Result<()> {
+    if changes.data {
+        eprintln!("Reloading data");
+        generator.reload_data()?;
+    }
+    if changes.templates {
+        eprintln!("Reloading templates");
+        generator.reload_templates()?;
+    }
+    for path in &changes.files {
+        eprintln!("Changed {}", path.display());
+    }
+    generator.build(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_changes() {
+        let generator =
+            SiteGenerator::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/site")).unwrap();
+        let site = Path::new("/site");
+        let output = Path::new("/site/output");
+        let mut changes = Changes::default();
+        let mut add = |path| changes.add(&generator, site, output, Path::new(path));
+        add("/site/output/index.html");
+        add("/site/.output.staging/index.html");
+        add("/elsewhere/file.txt");
+        assert!(changes.is_empty());
+
+        let mut add = |path| changes.add(&generator, site, output, Path::new(path));
+        add("/site/_templates/index.html");
+        add("/site/images/menu.svg");
+        add("/site/_headers");
+        assert_eq!(
+            changes,
+            Changes {
+                data: false,
+                templates: true,
+                files: BTreeSet::from([
+                    PathBuf::from("/site/_headers"),
+                    PathBuf::from("/site/images/menu.svg"),
+                ]),
+            }
+        );
+
+        changes.add(
+            &generator,
+            site,
+            output,
+            Path::new("/site/_data/restaurants.ron"),
+        );
+        assert!(changes.data);
+
+        let mut changes = Changes::default();
+        changes.add(&generator, site, output, Path::new("/site/_config.ron"));
+        assert!(changes.data && changes.files.is_empty());
+    }
+
+    #[test]
+    fn test_rebuild_removes_deleted() {
+        let site = crate::site::tests::empty_site();
+        let site = site.path();
+        let (page, image) = (
+            site.join("_templates/menu.html"),
+            site.join("images/menu.svg"),
+        );
+        fs::write(&page, "menu").unwrap();
+        fs::create_dir(site.join("images")).unwrap();
+        fs::write(&image, "<svg/>").unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let output = root.path().join("output");
+        let mut generator = SiteGenerator::new(site).unwrap();
+        generator.build(&output).unwrap();
+        assert!(output.join("menu.html").exists() && output.join("images/menu.svg").exists());
+
+        fs::remove_file(&page).unwrap();
+        fs::remove_dir_all(site.join("images")).unwrap();
+        let mut changes = Changes::default();
+        for path in [&page, &image] {
+            changes.add(&generator, site, &output, path);
+        }
+        rebuild(&mut generator, &output, &changes).unwrap();
+        assert!(!output.join("menu.html").exists());
+        assert!(!output.join("images").exists());
+        assert!(output.join("restaurant.json").exists());
+    }
+}
@@ -0,0 +1,68 @@
+// Runs site/filter.js against stand-in page elements for every day and hour option,
+// in each way a page can filter, and prints the filter keys shown for each as JSON.
+//
+// Usage: node filter_harness.js path/to/filter.js < input.json
+// where the input has `index` (filter-index.json), `elements` ({key, daytimes}),
+// `days` and `hours` (the filter option values). See the filter tests in src/model/filter.rs.
+const fs = require("fs");
+const vm = require("vm");
+
+const source = fs.readFileSync(process.argv[2], "utf8");
+const input = JSON.parse(fs.readFileSync(0, "utf8"));
+
+// "daytimes" reads data-daytimes, "index" reads filter-index.json, "failed" can't fetch it
+async function run(mode) {
+  const elements = input.elements.map(({ key, daytimes }) => {
+    const element = {
+      key,
+      dataset:
+        mode === "daytimes"
+          ? { daytimes: daytimes.join(" ") }
+          : { filter: key },
+      unmatched: false,
+    };
+    element.classList = {
+      toggle: (name, force) => {
+        if (name === "unmatched") element.unmatched = force;
+      },
+    };
+    return element;
+  });
+  const selects = { day: { value: "all" }, hour: { value: "all" } };
+  const attribute = { "[data-filter]": "filter", "[data-daytimes]": "daytimes" };
+  const context = {
+    document: {
+      documentElement: {
+        dataset: mode === "daytimes" ? {} : { filterIndex: "filter-index.json" },
+      },
+      getElementById: (id) => selects[id],
+      querySelectorAll: (selector) =>
+        elements.filter((e) => e.dataset[attribute[selector]] !== undefined),
+    },
+    window: { addEventListener: () => {} },
+    fetch: () =>
+      mode === "index"
+        ? Promise.resolve({ ok: true, json: async () => input.index })
+        : Promise.reject(new TypeError("Failed to fetch")),
+  };
+  vm.runInNewContext(source, context);
+
+  const shown = {};
+  for (const day of input.days) {
+    for (const hour of input.hours) {
+      selects.day.value = day;
+      selects.hour.value = hour;
+      await context.filter();
+      shown[`${day}-${hour}`] = elements.filter((e) => !e.unmatched).map((e) => e.key);
+    }
+  }
+  return shown;
+}
+
+(async () => {
+  const results = {};
+  for (const mode of ["daytimes", "index", "failed"]) {
+    results[mode] = await run(mode);
+  }
+  process.stdout.write(JSON.stringify(results));
+})();
@@ -1,3 +1,4 @@
+use chrono::NaiveDate;
 use sitegen::SiteGenerator;
 use std::{
     fs,
@@ -21,11 +22,39 @@ fn test_render_site() {
     if retain {
         eprintln!("TEMPDIR {}", output.display());
     }
-    let generator = SiteGenerator::new(&site).expect("SiteGenerator error");
+    let generator = SiteGenerator::new(&site)
+        .expect("SiteGenerator error")
+        .with_today(NaiveDate::from_ymd_opt(2025, 9, 1).unwrap())
+        .with_calendars(true)
+        .with_sitemap(true);
     generator.build(&output).expect("build failed");
 
     compare(&fixtures.join("index.html"), &output.join("index.html"));
     compare(&fixtures.join("style.css"), &output.join("style.css"));
+    compare(
+        &fixtures.join("calendar/the-black-swan.ics"),
+        &output.join("calendar/the-black-swan.ics"),
+    );
+    compare(
+        &fixtures.join("restaurant/the-black-swan.html"),
+        &output.join("restaurant/the-black-swan.html"),
+    );
+    compare(
+        &fixtures.join("restaurant/cross-orange.html"),
+        &output.join("restaurant/cross-orange.html"),
+    );
+    compare(&fixtures.join("robots.txt"), &output.join("robots.txt"));
+    // Only imported by other templates
+    assert!(!output.join("macros.html").exists());
+
+    // Static file dates depend on the checkout, only check generated pages
+    let sitemap = fs::read_to_string(output.join("sitemap.xml")).expect("sitemap");
+    assert!(sitemap.contains(
+        "<loc>https://asburypark.rectalogic.com/</loc>\n    <lastmod>2025-08-28</lastmod>"
+    ));
+    assert!(sitemap.contains(
+        "<loc>https://asburypark.rectalogic.com/restaurant/the-black-swan.html</loc>\n    <lastmod>2025-07-28</lastmod>"
+    ));
 }
 
 fn compare(fixture: &Path, actual: &Path) {
@@ -36,3 +65,39 @@ fn compare(fixture: &Path, actual: &Path) {
             .unwrap_or_else(|err| panic!("failed to read {} - {err:?}", actual.display()))
     );
 }
+
+#[test]
+fn test_feed_history() {
+    let site = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/site");
+    let output = Builder::new().tempdir().expect("tempdir failed");
+    let output = output.path();
+    let generator = SiteGenerator::new(&site)
+        .expect("SiteGenerator error")
+        .with_today(NaiveDate::from_ymd_opt(2025, 9, 1).unwrap())
+        .with_feed(true);
+    let feed = || fs::read_to_string(output.join("feed.xml")).expect("feed.xml");
+
+    // Nothing to compare against on the first build
+    generator.build(output).expect("build failed");
+    assert!(!feed().contains("<entry>"));
+
+    // Pretend the previous build did not list The Black Swan
+    let json_path = output.join("restaurant.json");
+    let mut previous: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+    previous
+        .as_array_mut()
+        .unwrap()
+        .retain(|restaurant| restaurant["name"] != "The Black Swan");
+    fs::write(&json_path, previous.to_string()).unwrap();
+
+    generator.build(output).expect("build failed");
+    assert!(feed().contains("<title>Added The Black Swan</title>"));
+    assert!(feed().contains(
+        "<link href=\"https://asburypark.rectalogic.com/restaurant/the-black-swan.html\"/>"
+    ));
+
+    // Entries are kept once there are no further changes
+    generator.build(output).expect("build failed");
+    assert_eq!(feed().matches("<entry>").count(), 1);
+}
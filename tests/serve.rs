@@ -0,0 +1,50 @@
+use sitegen::{PreviewServer, SiteGenerator};
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+    path::PathBuf,
+};
+
+#[test]
+fn test_serve_site() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let output = tempfile::tempdir().expect("tempdir failed");
+    let generator = SiteGenerator::new(root.join("tests/site")).expect("SiteGenerator error");
+    generator.build(output.path()).expect("build failed");
+
+    let server = PreviewServer::bind("127.0.0.1:0", output.path()).expect("bind failed");
+    let addr = server.addr().expect("server address");
+    let reloader = server.reloader();
+    std::thread::scope(|scope| {
+        scope.spawn(|| server.run());
+
+        let index = get(addr, "/");
+        assert!(index.starts_with("HTTP/1.1 200"), "{index}");
+        assert!(index.contains("Content-Type: text/html"));
+        assert!(index.contains(r#"fetch("/_livereload")"#));
+
+        let css = get(addr, "/style.css");
+        assert!(css.starts_with("HTTP/1.1 200"));
+        assert!(!css.contains("_livereload"));
+
+        assert!(get(addr, "/missing.html").starts_with("HTTP/1.1 404"));
+
+        assert!(get(addr, "/_livereload").ends_with("\r\n\r\n0"));
+        reloader.reload();
+        assert!(get(addr, "/_livereload").ends_with("\r\n\r\n1"));
+
+        server.shutdown();
+    });
+}
+
+fn get(addr: SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).expect("connect failed");
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n"
+    )
+    .expect("write failed");
+    let mut response = String::new();
+    stream.read_to_string(&mut response).expect("read failed");
+    response
+}